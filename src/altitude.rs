@@ -0,0 +1,262 @@
+//! Barometric altitude based on the ICAO standard atmosphere (ISA).
+//!
+//! Covers the troposphere, the tropopause and the stratosphere up to 47 km,
+//! which is well beyond the 10 mbar lower limit of the sensor. Altitudes are
+//! geopotential, as in the ISA tables. The math uses `libm` so it works the
+//! same in `no_std` builds.
+//!
+//! [`RelativeAltimeter`] tracks the height above a ground reference instead.
+
+use embedded_hal::delay::DelayNs;
+
+use crate::variant::{self, Variant};
+use crate::{Error, Interface, Ms5611, Ms5611Sample, SamplingConfig};
+
+/// Sea level pressure of the standard atmosphere, in pascals.
+pub const STANDARD_PRESSURE_PA: f32 = 101_325.0;
+
+/// g0 * M / R of the standard atmosphere, in K/m.
+const GMR: f32 = 0.034_163_2;
+
+/// Temperature gradient of the troposphere, in K/m.
+const TROPOSPHERE_LAPSE_RATE: f32 = -0.0065;
+
+/// A layer of the standard atmosphere.
+struct Layer {
+    /// Geopotential altitude of the layer's base, in metres.
+    base_altitude: f32,
+    /// Temperature at the base, in kelvin.
+    base_temperature: f32,
+    /// Temperature gradient, in K/m.
+    lapse_rate: f32,
+    /// Pressure at the base, in pascals.
+    base_pressure: f32,
+}
+
+const LAYERS: [Layer; 4] = [
+    // Troposphere
+    Layer {
+        base_altitude: 0.0,
+        base_temperature: 288.15,
+        lapse_rate: TROPOSPHERE_LAPSE_RATE,
+        base_pressure: STANDARD_PRESSURE_PA,
+    },
+    // Tropopause
+    Layer {
+        base_altitude: 11_000.0,
+        base_temperature: 216.65,
+        lapse_rate: 0.0,
+        base_pressure: 22_632.06,
+    },
+    // Stratosphere
+    Layer {
+        base_altitude: 20_000.0,
+        base_temperature: 216.65,
+        lapse_rate: 0.001,
+        base_pressure: 5_474.889,
+    },
+    Layer {
+        base_altitude: 32_000.0,
+        base_temperature: 228.65,
+        lapse_rate: 0.0028,
+        base_pressure: 868.018_7,
+    },
+];
+
+/// Altitude in metres at which the standard atmosphere has `pressure_pa`.
+pub fn pressure_altitude(pressure_pa: f32) -> f32 {
+    let layer = LAYERS.iter()
+        .rev()
+        .find(|l| pressure_pa <= l.base_pressure)
+        .unwrap_or(&LAYERS[0]);
+
+    let ratio = pressure_pa / layer.base_pressure;
+    if layer.lapse_rate == 0.0 {
+        layer.base_altitude
+            - layer.base_temperature / GMR * libm::logf(ratio)
+    } else {
+        layer.base_altitude + layer.base_temperature / layer.lapse_rate
+            * (libm::powf(ratio, -layer.lapse_rate / GMR) - 1.0)
+    }
+}
+
+/// Pressure in pascals of the standard atmosphere at `altitude_m`. Inverse
+/// of `pressure_altitude`.
+pub fn pressure_at_altitude(altitude_m: f32) -> f32 {
+    let layer = LAYERS.iter()
+        .rev()
+        .find(|l| altitude_m >= l.base_altitude)
+        .unwrap_or(&LAYERS[0]);
+
+    let height = altitude_m - layer.base_altitude;
+    if layer.lapse_rate == 0.0 {
+        layer.base_pressure
+            * libm::expf(-GMR * height / layer.base_temperature)
+    } else {
+        let temperature = layer.base_temperature + layer.lapse_rate * height;
+        layer.base_pressure * libm::powf(
+            layer.base_temperature / temperature, GMR / layer.lapse_rate)
+    }
+}
+
+/// Converts pressure to altitude relative to a reference pressure.
+///
+/// With the sea level pressure (QNH) as reference, the altitude is above mean
+/// sea level. With the pressure at an aerodrome (QFE), it is the height above
+/// it. The altitude is the difference of the standard atmosphere altitudes of
+/// the measured and the reference pressure.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Altimeter {
+    reference_pa: f32,
+    /// Cached `pressure_altitude(reference_pa)`.
+    reference_altitude: f32,
+}
+
+impl Altimeter {
+    /// Creates an altimeter with `reference_pa` being the pressure at zero
+    /// altitude, in pascals.
+    pub fn new(reference_pa: f32) -> Self {
+        Altimeter {
+            reference_pa,
+            reference_altitude: pressure_altitude(reference_pa),
+        }
+    }
+
+    /// Altimeter referenced to the standard sea level pressure.
+    pub fn standard() -> Self {
+        Self::new(STANDARD_PRESSURE_PA)
+    }
+
+    /// Reference pressure in pascals.
+    pub fn reference_pa(&self) -> f32 {
+        self.reference_pa
+    }
+
+    /// Altitude in metres at which `pressure_pa` is measured.
+    pub fn altitude(&self, pressure_pa: f32) -> f32 {
+        pressure_altitude(pressure_pa) - self.reference_altitude
+    }
+
+    /// Altitude in metres of a sample.
+    pub fn sample_altitude(&self, sample: &Ms5611Sample) -> f32 {
+        self.altitude(sample.pressure_pa as f32)
+    }
+
+    /// Pressure in pascals expected at `altitude_m`. Inverse of `altitude`.
+    pub fn pressure(&self, altitude_m: f32) -> f32 {
+        pressure_at_altitude(altitude_m + self.reference_altitude)
+    }
+}
+
+impl Default for Altimeter {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Sample along with the height above the ground reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelativeSample {
+    pub sample: Ms5611Sample,
+    /// Height above the ground reference, in metres.
+    pub height_m: f32,
+}
+
+/// Tracks the height above a ground reference, e.g. the takeoff point.
+///
+/// The reference pressure and temperature are averaged over a number of
+/// samples when zeroing. The height is computed with the hypsometric formula
+/// using the ground temperature instead of the standard atmosphere's, which is
+/// more accurate close to the ground.
+pub struct RelativeAltimeter<B, V = variant::Ms5611> {
+    ms5611: Ms5611<B, V>,
+    sampling: SamplingConfig,
+    ground_pressure_pa: f32,
+    /// In kelvin.
+    ground_temperature: f32,
+}
+
+impl<B, E, V> RelativeAltimeter<B, V>
+where
+  B: Interface<Error = E>,
+  V: Variant,
+{
+    /// Wraps the driver and zeroes on the average of `samples` samples.
+    /// `sampling` is used for all reads, as in `Ms5611::read_sample`.
+    pub fn new<S, D>(ms5611: Ms5611<B, V>, sampling: S, samples: u16,
+                     delay: &mut D)
+            -> Result<Self, Error<E>>
+    where S: Into<SamplingConfig>, D: DelayNs
+    {
+        let mut altimeter = RelativeAltimeter {
+            ms5611,
+            sampling: sampling.into(),
+            ground_pressure_pa: STANDARD_PRESSURE_PA,
+            ground_temperature: 288.15,
+        };
+        altimeter.zero(samples, delay)?;
+        Ok(altimeter)
+    }
+
+    /// Re-establishes the ground reference from the average of `samples`
+    /// samples (at least one). Can be called at any time, e.g. in flight.
+    pub fn zero<D>(&mut self, samples: u16, delay: &mut D)
+            -> Result<(), Error<E>>
+    where D: DelayNs
+    {
+        let samples = samples.max(1);
+        let mut pressure_sum = 0i64;
+        let mut temperature_sum = 0i64;
+        for _ in 0 .. samples {
+            let sample = self.ms5611.read_sample(self.sampling, delay)?;
+            pressure_sum += sample.pressure_pa as i64;
+            temperature_sum += sample.temperature_centi_c as i64;
+        }
+
+        self.ground_pressure_pa = pressure_sum as f32 / samples as f32;
+        self.ground_temperature =
+            temperature_sum as f32 / samples as f32 / 100.0 + 273.15;
+        Ok(())
+    }
+
+    /// Reads a sample and computes its height above the ground reference.
+    pub fn read_sample<D>(&mut self, delay: &mut D)
+            -> Result<RelativeSample, Error<E>>
+    where D: DelayNs
+    {
+        let sample = self.ms5611.read_sample(self.sampling, delay)?;
+
+        Ok(RelativeSample {
+            sample,
+            height_m: self.height(sample.pressure_pa as f32),
+        })
+    }
+
+    /// Height in metres above the ground reference at which `pressure_pa`
+    /// is measured.
+    pub fn height(&self, pressure_pa: f32) -> f32 {
+        let ratio = pressure_pa / self.ground_pressure_pa;
+        self.ground_temperature / TROPOSPHERE_LAPSE_RATE
+            * (libm::powf(ratio, -TROPOSPHERE_LAPSE_RATE / GMR) - 1.0)
+    }
+
+    /// Averaged ground reference pressure, in pascals.
+    pub fn ground_pressure_pa(&self) -> f32 {
+        self.ground_pressure_pa
+    }
+
+    /// Averaged ground reference temperature, in celsius.
+    pub fn ground_temperature_c(&self) -> f32 {
+        self.ground_temperature - 273.15
+    }
+
+    /// Underlying driver.
+    pub fn ms5611(&mut self) -> &mut Ms5611<B, V> {
+        &mut self.ms5611
+    }
+
+    /// Releases the underlying driver.
+    pub fn release(self) -> Ms5611<B, V> {
+        self.ms5611
+    }
+}
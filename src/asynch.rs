@@ -0,0 +1,134 @@
+//! Async driver on top of embedded-hal-async.
+
+use core::marker::PhantomData;
+
+use byteorder::{ByteOrder, BigEndian};
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+use embedded_hal_async::spi::SpiDevice;
+
+use crate::interface::AsyncInterface;
+use crate::prom::Prom;
+use crate::variant::{self, Variant};
+use crate::{check_osr, Error, I2cInterface, Ms5611Reg, Ms5611Sample, SamplingConfig, SpiInterface};
+
+/// Pressure sensor, awaiting the bus and the conversion delays instead of
+/// blocking.
+///
+/// Performs the same PROM validation and compensation as
+/// [`Ms5611`](crate::Ms5611).
+pub struct Ms5611Async<B, V = variant::Ms5611> {
+    bus: B,
+    prom: Prom,
+    variant: PhantomData<V>,
+}
+
+impl<I: I2c> Ms5611Async<I2cInterface<I>> {
+
+    /// If i2c_addr is unspecified, 0x77 is used.
+    /// The addr of the device is 0x77 if CSB is low / 0x76 if CSB is high.
+    pub async fn new(i2c: I, i2c_addr: Option<u8>)
+            -> Result<Self, Error<I::Error>> {
+        let address = i2c_addr.unwrap_or(0x77);
+
+        Self::from_interface(I2cInterface::new(i2c, address)).await
+    }
+}
+
+impl<S: SpiDevice> Ms5611Async<SpiInterface<S>> {
+    /// The CSB pin of the device must be the chip select of `spi`.
+    pub async fn new_spi(spi: S) -> Result<Self, Error<S::Error>> {
+        Self::from_interface(SpiInterface::new(spi)).await
+    }
+}
+
+impl<B, E, V> Ms5611Async<B, V>
+where
+  B: AsyncInterface<Error = E>,
+  V: Variant,
+{
+
+    /// Creates the driver on top of an already configured transport.
+    pub async fn from_interface(mut bus: B) -> Result<Self, Error<E>> {
+        let prom = Self::read_prom(&mut bus).await?;
+
+        Ok(Ms5611Async {
+            bus,
+            prom,
+            variant: PhantomData,
+        })
+    }
+
+    /// Triggers a hardware reset of the device.
+    pub async fn reset<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where D: DelayNs
+    {
+        self.bus.write(Ms5611Reg::Reset.addr()).await.map_err(Error::Bus)?;
+        delay.delay_ms(50).await;
+        Ok(())
+    }
+
+    async fn read_prom(bus: &mut B) -> Result<Prom, Error<E>> {
+        let mut words = [0u16; 8];
+        let mut buf = [0u8; 2];
+        for (i, word) in words.iter_mut().enumerate().take(V::PROM_LAYOUT.len()) {
+            bus.write_read(Ms5611Reg::Prom.addr() + 2 * i as u8, &mut buf)
+                .await
+                .map_err(Error::Bus)?;
+            *word = BigEndian::read_u16(&buf);
+        }
+
+        Prom::from_words_for::<V>(words).map_err(Error::widen)
+    }
+
+    /// Factory calibration data read from the device.
+    pub fn prom(&self) -> &Prom {
+        &self.prom
+    }
+
+    async fn read_adc(&mut self) -> Result<u32, Error<E>> {
+        let mut buf = [0u8; 4];
+        self.bus.write_read(Ms5611Reg::AdcRead.addr(), &mut buf[1 .. 4])
+            .await
+            .map_err(Error::Bus)?;
+        let adc = BigEndian::read_u32(&buf);
+        if adc == 0 {
+            return Err(Error::AdcNotReady);
+        }
+        Ok(adc)
+    }
+
+    /// Awaits between 2ms (OSR=256) and 20ms (OSR=4096), or 40ms with
+    /// OSR=8192, for the two conversions, yielding to the executor in the
+    /// meantime.
+    ///
+    /// `sampling` is either an `Osr` used for both conversions or a
+    /// `SamplingConfig` with separate ratios for pressure and temperature.
+    pub async fn read_sample<S, D>(&mut self, sampling: S, delay: &mut D)
+            -> Result<Ms5611Sample, Error<E>>
+    where S: Into<SamplingConfig>, D: DelayNs
+    {
+        let SamplingConfig { pressure, temperature } = sampling.into();
+        check_osr::<V, E>(pressure)?;
+        check_osr::<V, E>(temperature)?;
+
+        self.bus.write(Ms5611Reg::D1.addr() + pressure.addr_modifier())
+            .await
+            .map_err(Error::Bus)?;
+        delay.delay_ms(pressure.conversion_time_ms()).await;
+
+        // Raw digital pressure
+        let d1 = self.read_adc().await?;
+
+        self.bus.write(Ms5611Reg::D2.addr() + temperature.addr_modifier())
+            .await
+            .map_err(Error::Bus)?;
+        delay.delay_ms(temperature.conversion_time_ms()).await;
+
+        // Raw digital temperature
+        let d2 = self.read_adc().await?;
+
+        Ok(self.prom.compensate_for::<V>(d1, d2))
+    }
+}
@@ -0,0 +1,36 @@
+//! Time source for timestamping conversions.
+
+/// Monotonic clock read by the driver around conversions, see
+/// [`Ms5611::read_sample_timestamped`](crate::Ms5611::read_sample_timestamped).
+///
+/// The driver only copies the instants into the sample, so any
+/// representation works, e.g. ticks of a hardware timer or a wrapping
+/// microsecond counter. Closures returning the current time implement the
+/// trait as well.
+pub trait Clock {
+    type Instant: Copy;
+
+    /// Current time.
+    fn now(&mut self) -> Self::Instant;
+}
+
+impl<F, I> Clock for F
+where
+  F: FnMut() -> I,
+  I: Copy,
+{
+    type Instant = I;
+
+    fn now(&mut self) -> I {
+        self()
+    }
+}
+
+/// Clock for conversions that aren't timestamped.
+pub(crate) struct NoClock;
+
+impl Clock for NoClock {
+    type Instant = ();
+
+    fn now(&mut self) {}
+}
@@ -0,0 +1,82 @@
+//! Water depth for submersible parts such as the MS5803 and MS5837.
+
+use crate::Ms5611Sample;
+
+/// Standard gravity, in m/s².
+const GRAVITY: f32 = 9.806_65;
+
+/// Fluid the sensor is submerged in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fluid {
+    /// 997 kg/m³.
+    FreshWater,
+    /// 1029 kg/m³.
+    SaltWater,
+    /// Density in kg/m³.
+    Custom(f32),
+}
+
+impl Fluid {
+    /// Density in kg/m³.
+    pub fn density(&self) -> f32 {
+        match *self {
+            Fluid::FreshWater => 997.0,
+            Fluid::SaltWater => 1029.0,
+            Fluid::Custom(density) => density,
+        }
+    }
+}
+
+/// Converts pressure to depth below the surface.
+///
+/// The surface pressure should be captured from a sample taken right before
+/// the dive, as it varies with weather and altitude.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthGauge {
+    fluid: Fluid,
+    surface_pressure_pa: f32,
+}
+
+impl DepthGauge {
+    /// `surface_pressure_pa` is the atmospheric pressure at the surface, in
+    /// pascals.
+    pub fn new(fluid: Fluid, surface_pressure_pa: f32) -> Self {
+        DepthGauge { fluid, surface_pressure_pa }
+    }
+
+    /// Uses a pre-dive sample as the surface reference.
+    pub fn from_surface_sample(fluid: Fluid, sample: &Ms5611Sample) -> Self {
+        Self::new(fluid, sample.pressure_pa as f32)
+    }
+
+    /// Re-captures the surface reference, e.g. before the next dive.
+    pub fn set_surface(&mut self, sample: &Ms5611Sample) {
+        self.surface_pressure_pa = sample.pressure_pa as f32;
+    }
+
+    pub fn fluid(&self) -> Fluid {
+        self.fluid
+    }
+
+    /// Surface reference pressure, in pascals.
+    pub fn surface_pressure_pa(&self) -> f32 {
+        self.surface_pressure_pa
+    }
+
+    /// Depth in metres at which `pressure_pa` is measured. Negative above
+    /// the surface.
+    pub fn depth(&self, pressure_pa: f32) -> f32 {
+        (pressure_pa - self.surface_pressure_pa)
+            / (self.fluid.density() * GRAVITY)
+    }
+
+    /// Depth in metres of a sample.
+    pub fn sample_depth(&self, sample: &Ms5611Sample) -> f32 {
+        self.depth(sample.pressure_pa as f32)
+    }
+
+    /// Pressure in pascals expected at `depth_m`. Inverse of `depth`.
+    pub fn pressure(&self, depth_m: f32) -> f32 {
+        self.surface_pressure_pa + depth_m * self.fluid.density() * GRAVITY
+    }
+}
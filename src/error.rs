@@ -0,0 +1,56 @@
+//! Errors reported by the driver.
+
+use core::convert::Infallible;
+use core::fmt;
+
+/// Error returned by all fallible driver operations.
+///
+/// `E` is the error type of the underlying bus transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// Error on the underlying bus.
+    Bus(E),
+    /// The CRC nibble stored in the PROM does not match the CRC computed over
+    /// its contents. Usually a sign of a marginal connection.
+    Crc {
+        /// CRC stored in the PROM.
+        expected: u8,
+        /// CRC computed from the PROM contents.
+        computed: u8,
+    },
+    /// The PROM contents are implausible (e.g. all bits cleared or set), which
+    /// the CRC alone cannot detect, or a cached PROM doesn't match the device.
+    InvalidProm,
+    /// The ADC was read before the conversion completed and returned 0.
+    AdcNotReady,
+    /// The oversampling ratio isn't offered by the sensor part.
+    UnsupportedOsr,
+}
+
+impl Error<Infallible> {
+    /// Converts an error that can't stem from a bus into any `Error<E>`.
+    pub(crate) fn widen<E>(self) -> Error<E> {
+        match self {
+            Error::Bus(never) => match never {},
+            Error::Crc { expected, computed } =>
+                Error::Crc { expected, computed },
+            Error::InvalidProm => Error::InvalidProm,
+            Error::AdcNotReady => Error::AdcNotReady,
+            Error::UnsupportedOsr => Error::UnsupportedOsr,
+        }
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Bus(e) => write!(f, "bus error: {:?}", e),
+            Error::Crc { expected, computed } => write!(
+                f, "PROM CRC did not match: {} != {}", expected, computed),
+            Error::InvalidProm => write!(f, "invalid PROM contents"),
+            Error::AdcNotReady => write!(f, "ADC conversion not ready"),
+            Error::UnsupportedOsr =>
+                write!(f, "oversampling ratio not supported by the part"),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Rejection of pressure spikes, e.g. from bus glitches or prop wash.
+//!
+//! [`SpikeFilter`] combines two stages, both optional: a rate-of-change gate
+//! that drops samples whose pressure jumped further than physically
+//! plausible since the last accepted one, and a median over the last `N`
+//! accepted samples that smooths out what slips through.
+
+use crate::{median, Ms5611Sample};
+
+/// Bounds of the pressure change between accepted samples.
+///
+/// A sample is rejected if its pressure differs from the last accepted one
+/// by more than `tolerance_pa + max_rate_pa_per_s * dt`. Since `dt` grows
+/// while samples are rejected, a genuine step is accepted eventually.
+///
+/// The first sample can't be checked against anything. So that a spike there
+/// doesn't lock out the genuine samples that follow, the filter starts over
+/// with the next sample once `max_consecutive_rejections` were rejected in a
+/// row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateGate {
+    /// Largest plausible rate of change. Close to sea level, 12 Pa/s
+    /// correspond to 1 m/s of vertical speed.
+    pub max_rate_pa_per_s: u32,
+    /// Allowance for noise, independent of the time between samples.
+    pub tolerance_pa: u32,
+    /// Number of samples rejected in a row before the filter starts over.
+    pub max_consecutive_rejections: u32,
+}
+
+impl Default for RateGate {
+    fn default() -> Self {
+        RateGate {
+            // ~100 m/s, beyond anything a small aircraft does.
+            max_rate_pa_per_s: 1200,
+            tolerance_pa: 20,
+            max_consecutive_rejections: 5,
+        }
+    }
+}
+
+/// Median-of-`N` filter with optional rate-of-change gating.
+///
+/// `N` of 1 disables the median, leaving the gate only.
+#[derive(Debug, Clone)]
+pub struct SpikeFilter<const N: usize> {
+    gate: Option<RateGate>,
+    /// Last accepted sample and its timestamp in milliseconds.
+    last: Option<(u32, Ms5611Sample)>,
+    /// Ring buffer of accepted samples, valid up to `len`.
+    window: [Ms5611Sample; N],
+    next: usize,
+    len: usize,
+    rejected: u32,
+    /// Samples rejected since the last accepted one.
+    consecutive_rejections: u32,
+}
+
+impl<const N: usize> SpikeFilter<N> {
+    /// Panics if `N` is 0.
+    pub fn new(gate: Option<RateGate>) -> Self {
+        assert!(N > 0, "median window without capacity");
+        let empty = Ms5611Sample {
+            pressure_pa: 0,
+            temperature_centi_c: 0,
+        };
+
+        SpikeFilter {
+            gate,
+            last: None,
+            window: [empty; N],
+            next: 0,
+            len: 0,
+            rejected: 0,
+            consecutive_rejections: 0,
+        }
+    }
+
+    /// Feeds a sample captured at `timestamp_ms` (free-running, wrapping
+    /// millisecond clock).
+    ///
+    /// Returns the median of the accepted samples in the window, or `None`
+    /// if the sample was rejected. The first sample is always accepted, and
+    /// so is the one after `max_consecutive_rejections` rejected samples,
+    /// which replaces the samples in the window.
+    pub fn update(&mut self, timestamp_ms: u32, sample: &Ms5611Sample)
+            -> Option<Ms5611Sample> {
+        if let (Some(gate), Some((last_ms, last))) = (self.gate, self.last) {
+            let dt_ms = timestamp_ms.wrapping_sub(last_ms) as u64;
+            let allowed = gate.tolerance_pa as u64
+                + gate.max_rate_pa_per_s as u64 * dt_ms / 1000;
+            let jump = (sample.pressure_pa as i64 - last.pressure_pa as i64)
+                .unsigned_abs();
+            if jump > allowed {
+                if self.consecutive_rejections < gate.max_consecutive_rejections {
+                    self.consecutive_rejections += 1;
+                    self.rejected = self.rejected.saturating_add(1);
+                    return None;
+                }
+                self.reset();
+            }
+        }
+        self.consecutive_rejections = 0;
+
+        self.last = Some((timestamp_ms, *sample));
+        self.window[self.next] = *sample;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+
+        Some(self.median())
+    }
+
+    fn median(&self) -> Ms5611Sample {
+        let mut pressure = [0i64; N];
+        let mut temperature = [0i64; N];
+        for (i, sample) in self.window[.. self.len].iter().enumerate() {
+            pressure[i] = sample.pressure_pa as i64;
+            temperature[i] = sample.temperature_centi_c as i64;
+        }
+
+        Ms5611Sample {
+            pressure_pa: median(&mut pressure[.. self.len]) as i32,
+            temperature_centi_c: median(&mut temperature[.. self.len]) as i32,
+        }
+    }
+
+    /// Number of samples rejected by the gate so far.
+    pub fn rejected(&self) -> u32 {
+        self.rejected
+    }
+
+    /// Resets the number of rejected samples.
+    pub fn clear_rejected(&mut self) {
+        self.rejected = 0;
+    }
+
+    /// Forgets the accepted samples, e.g. after a gap in the samples. The
+    /// next sample is accepted unconditionally.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.next = 0;
+        self.len = 0;
+        self.consecutive_rejections = 0;
+    }
+}
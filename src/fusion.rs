@@ -0,0 +1,144 @@
+//! Kalman filter fusing barometric altitude with vertical acceleration.
+//!
+//! The barometer is accurate in the long run but noisy and lagging once
+//! filtered, the accelerometer is responsive but drifts when integrated. The
+//! filter estimates altitude, vertical velocity and the accelerometer bias
+//! from both, using fixed-size matrices only.
+
+/// Noise parameters of the filter, as standard deviations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KalmanConfig {
+    /// Accelerometer noise, in m/s².
+    pub accel_noise: f32,
+    /// Random walk of the accelerometer bias, in m/s² per √s.
+    pub bias_noise: f32,
+    /// Noise of the barometric altitude, in m.
+    pub baro_noise: f32,
+}
+
+impl Default for KalmanConfig {
+    fn default() -> Self {
+        KalmanConfig {
+            accel_noise: 0.3,
+            bias_noise: 0.01,
+            // The MS5611 at OSR 4096 is ~1 Pa RMS, i.e. ~10 cm, when the air
+            // is still. In flight, turbulence and prop wash around the sensor
+            // easily triple that.
+            baro_noise: 0.3,
+        }
+    }
+}
+
+type Vector = [f32; 3];
+type Matrix = [[f32; 3]; 3];
+
+/// Altitude, vertical velocity and accelerometer bias estimator.
+///
+/// Call [`predict`](Self::predict) for every accelerometer reading and
+/// [`update`](Self::update) for every barometric altitude, e.g. from
+/// [`altitude::Altimeter`](crate::altitude::Altimeter).
+#[derive(Debug, Clone)]
+pub struct AltitudeKalman {
+    config: KalmanConfig,
+    /// Altitude (m), vertical velocity (m/s), accelerometer bias (m/s²).
+    x: Vector,
+    /// State covariance.
+    p: Matrix,
+}
+
+impl AltitudeKalman {
+    pub fn new(config: KalmanConfig, initial_altitude_m: f32) -> Self {
+        let baro_variance = config.baro_noise * config.baro_noise;
+        AltitudeKalman {
+            config,
+            x: [initial_altitude_m, 0.0, 0.0],
+            p: [
+                [baro_variance, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Propagates the state by `dt` seconds with the vertical acceleration
+    /// `accel` in m/s² (up positive, gravity removed).
+    pub fn predict(&mut self, accel: f32, dt: f32) {
+        let dt2 = dt * dt / 2.0;
+        let [h, v, b] = self.x;
+        let a = accel - b;
+        self.x = [h + v * dt + a * dt2, v + a * dt, b];
+
+        let f: Matrix = [
+            [1.0, dt, -dt2],
+            [0.0, 1.0, -dt],
+            [0.0, 0.0, 1.0],
+        ];
+        let mut p = mul(&mul(&f, &self.p), &transpose(&f));
+
+        // Acceleration noise enters like the acceleration itself.
+        let g: Vector = [dt2, dt, 0.0];
+        let accel_variance = self.config.accel_noise * self.config.accel_noise;
+        for (i, row) in p.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell += g[i] * g[j] * accel_variance;
+            }
+        }
+        p[2][2] += self.config.bias_noise * self.config.bias_noise * dt;
+
+        self.p = p;
+    }
+
+    /// Corrects the state with a barometric altitude in metres.
+    pub fn update(&mut self, altitude_m: f32) {
+        let baro_variance = self.config.baro_noise * self.config.baro_noise;
+        let innovation = altitude_m - self.x[0];
+        let s = self.p[0][0] + baro_variance;
+        let k: Vector = [self.p[0][0] / s, self.p[1][0] / s, self.p[2][0] / s];
+
+        for (x, k) in self.x.iter_mut().zip(k.iter()) {
+            *x += k * innovation;
+        }
+
+        let row0 = self.p[0];
+        for (row, k) in self.p.iter_mut().zip(k.iter()) {
+            for (cell, p0) in row.iter_mut().zip(row0.iter()) {
+                *cell -= k * p0;
+            }
+        }
+    }
+
+    /// Estimated altitude in metres.
+    pub fn altitude(&self) -> f32 {
+        self.x[0]
+    }
+
+    /// Estimated vertical velocity in m/s.
+    pub fn vertical_speed(&self) -> f32 {
+        self.x[1]
+    }
+
+    /// Estimated accelerometer bias in m/s².
+    pub fn accel_bias(&self) -> f32 {
+        self.x[2]
+    }
+}
+
+fn mul(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut c = [[0.0; 3]; 3];
+    for (i, row) in c.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0 .. 3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    c
+}
+
+fn transpose(a: &Matrix) -> Matrix {
+    let mut t = [[0.0; 3]; 3];
+    for (i, row) in a.iter().enumerate() {
+        for (j, &cell) in row.iter().enumerate() {
+            t[j][i] = cell;
+        }
+    }
+    t
+}
@@ -0,0 +1,116 @@
+//! Bus transports over which the MS5611 command set is issued.
+
+use embedded_hal::i2c::I2c;
+use embedded_hal::spi::{Operation, SpiDevice};
+
+/// Transport used by the driver to talk to the device.
+///
+/// The MS5611 command set is identical on both buses: every transaction
+/// starts with a single command byte, optionally followed by reading back
+/// the response.
+pub trait Interface {
+    type Error;
+
+    /// Sends a single command byte.
+    fn write(&mut self, cmd: u8) -> Result<(), Self::Error>;
+
+    /// Sends a command byte and reads the response into `buf`.
+    fn write_read(&mut self, cmd: u8, buf: &mut [u8])
+            -> Result<(), Self::Error>;
+}
+
+/// I2C transport.
+pub struct I2cInterface<I> {
+    i2c: I,
+    address: u8,
+}
+
+impl<I> I2cInterface<I> {
+    /// The addr of the device is 0x77 if CSB is low / 0x76 if CSB is high.
+    pub fn new(i2c: I, address: u8) -> Self {
+        I2cInterface { i2c, address }
+    }
+}
+
+impl<I: I2c> Interface for I2cInterface<I> {
+    type Error = I::Error;
+
+    fn write(&mut self, cmd: u8) -> Result<(), I::Error> {
+        self.i2c.write(self.address, &[cmd])
+    }
+
+    fn write_read(&mut self, cmd: u8, buf: &mut [u8])
+            -> Result<(), I::Error> {
+        self.i2c.write_read(self.address, &[cmd], buf)
+    }
+}
+
+/// SPI transport.
+///
+/// The `SpiDevice` must drive the CSB pin as its chip select. The device
+/// supports SPI mode 0 and 3.
+pub struct SpiInterface<S> {
+    spi: S,
+}
+
+impl<S> SpiInterface<S> {
+    pub fn new(spi: S) -> Self {
+        SpiInterface { spi }
+    }
+}
+
+impl<S: SpiDevice> Interface for SpiInterface<S> {
+    type Error = S::Error;
+
+    fn write(&mut self, cmd: u8) -> Result<(), S::Error> {
+        self.spi.write(&[cmd])
+    }
+
+    fn write_read(&mut self, cmd: u8, buf: &mut [u8])
+            -> Result<(), S::Error> {
+        self.spi.transaction(&mut [Operation::Write(&[cmd]), Operation::Read(buf)])
+    }
+}
+
+/// Async counterpart of [`Interface`].
+#[cfg(feature = "async")]
+#[allow(async_fn_in_trait)]
+pub trait AsyncInterface {
+    type Error;
+
+    /// Sends a single command byte.
+    async fn write(&mut self, cmd: u8) -> Result<(), Self::Error>;
+
+    /// Sends a command byte and reads the response into `buf`.
+    async fn write_read(&mut self, cmd: u8, buf: &mut [u8])
+            -> Result<(), Self::Error>;
+}
+
+#[cfg(feature = "async")]
+impl<I: embedded_hal_async::i2c::I2c> AsyncInterface for I2cInterface<I> {
+    type Error = I::Error;
+
+    async fn write(&mut self, cmd: u8) -> Result<(), I::Error> {
+        self.i2c.write(self.address, &[cmd]).await
+    }
+
+    async fn write_read(&mut self, cmd: u8, buf: &mut [u8])
+            -> Result<(), I::Error> {
+        self.i2c.write_read(self.address, &[cmd], buf).await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<S: embedded_hal_async::spi::SpiDevice> AsyncInterface for SpiInterface<S> {
+    type Error = S::Error;
+
+    async fn write(&mut self, cmd: u8) -> Result<(), S::Error> {
+        self.spi.write(&[cmd]).await
+    }
+
+    async fn write_read(&mut self, cmd: u8, buf: &mut [u8])
+            -> Result<(), S::Error> {
+        self.spi.transaction(&mut [Operation::Write(&[cmd]), Operation::Read(buf)])
+            .await
+    }
+}
@@ -1,52 +1,170 @@
 //! A library for the MS5611 barometric pressure sensor.
+//!
+//! The device can be connected over either I2C or SPI, see [`Interface`].
 
 #![no_std]
 
+use core::marker::PhantomData;
+
 use byteorder::{ByteOrder, BigEndian};
 
-use embedded_hal::blocking::delay::DelayMs;
-use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::I2c;
+use embedded_hal::spi::SpiDevice;
+
+use crate::clock::NoClock;
+
+#[cfg(feature = "float")]
+pub mod altitude;
+#[cfg(feature = "async")]
+mod asynch;
+mod clock;
+#[cfg(feature = "float")]
+pub mod depth;
+mod error;
+pub mod filter;
+#[cfg(feature = "fusion")]
+pub mod fusion;
+mod interface;
+mod prom;
+pub mod sampler;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod variant;
+#[cfg(feature = "float")]
+pub mod variometer;
+
+#[cfg(feature = "async")]
+pub use crate::asynch::Ms5611Async;
+pub use crate::clock::Clock;
+pub use crate::error::Error;
+#[cfg(feature = "async")]
+pub use crate::interface::AsyncInterface;
+pub use crate::interface::{I2cInterface, Interface, SpiInterface};
+pub use crate::prom::Prom;
+pub use crate::variant::Variant;
 
 /// Oversampling ratio
 /// See datasheet for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Osr {
     Opt256,
     Opt512,
     Opt1024,
     Opt2048,
     Opt4096,
+    /// Only offered by some parts, see [`Variant::SUPPORTS_OSR_8192`].
+    Opt8192,
 }
 
 impl Osr {
-    fn get_delay(&self) -> u8 {
+    /// Time in milliseconds to wait for a conversion to complete.
+    pub fn conversion_time_ms(&self) -> u32 {
         match *self {
             Osr::Opt256 => 1,
             Osr::Opt512 => 2,
             Osr::Opt1024 => 3,
             Osr::Opt2048 => 5,
             Osr::Opt4096 => 10,
+            Osr::Opt8192 => 20,
         }
     }
 
-    fn addr_modifier(&self) -> u8 {
+    pub(crate) fn addr_modifier(&self) -> u8 {
         match *self {
             Osr::Opt256 => 0,
             Osr::Opt512 => 2,
             Osr::Opt1024 => 4,
             Osr::Opt2048 => 6,
             Osr::Opt4096 => 8,
+            Osr::Opt8192 => 10,
+        }
+    }
+}
+
+/// Oversampling ratios of the pressure (D1) and temperature (D2)
+/// conversions of a sample.
+///
+/// The temperature changes slowly and affects the pressure only through the
+/// compensation, so converting it with a lower ratio than the pressure saves
+/// time at little loss of precision. An `Osr` converts into a configuration
+/// using it for both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplingConfig {
+    pub pressure: Osr,
+    pub temperature: Osr,
+}
+
+impl SamplingConfig {
+    /// Time in milliseconds to wait for both conversions to complete.
+    pub fn conversion_time_ms(&self) -> u32 {
+        self.pressure.conversion_time_ms() + self.temperature.conversion_time_ms()
+    }
+}
+
+impl From<Osr> for SamplingConfig {
+    fn from(osr: Osr) -> Self {
+        SamplingConfig {
+            pressure: osr,
+            temperature: osr,
         }
     }
 }
 
 /// Pressure sensor
-pub struct Ms5611<I> {
-    i2c: I,
-    address : u8,
-    prom: Prom
+///
+/// Other parts of the sensor family are supported through the [`Variant`]
+/// type parameter, see [`variant`].
+pub struct Ms5611<B, V = variant::Ms5611> {
+    bus: B,
+    prom: Prom,
+    /// Oversampling ratios used by `poll` and `read_scheduled`.
+    sampling: SamplingConfig,
+    /// Pressure conversions sharing one temperature conversion.
+    temperature_ratio: u16,
+    /// Last D2 converted by the scheduler.
+    last_d2: Option<u32>,
+    /// Pressure conversions compensated with `last_d2` so far.
+    pressures_since_temperature: u16,
+    retry: RetryPolicy,
+    conversion: Conversion,
+    variant: PhantomData<V>,
+}
+
+/// How blocking reads recover from a conversion result read as 0.
+///
+/// A zero result means the ADC was read before the conversion completed,
+/// typically because the delay provider sleeps shorter than requested. With
+/// retries enabled, the conversion is restarted and awaited `extra_delay_ms`
+/// longer than nominal before giving up with `Error::AdcNotReady`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetryPolicy {
+    /// Conversions restarted after the first zero result. 0 disables retries.
+    pub retries: u8,
+    /// Added to the conversion time when waiting for a restarted conversion,
+    /// in milliseconds.
+    pub extra_delay_ms: u32,
+}
+
+/// Conversion in flight, as tracked by `poll`.
+enum Conversion {
+    Idle,
+    Pressure { ready_at: u32 },
+    Temperature { d1: u32, ready_at: u32 },
+}
+
+/// Progress of a sample acquired with [`Ms5611::poll`], or
+/// [`Ms5611::poll_raw`] for `T = RawSample`.
+#[derive(Debug)]
+pub enum Poll<T = Ms5611Sample> {
+    /// A conversion is in progress. Poll again once the clock reaches
+    /// `ready_at` (milliseconds).
+    Pending { ready_at: u32 },
+    /// The conversions of a sample completed.
+    Ready(T),
 }
 
-enum Ms5611Reg {
+pub(crate) enum Ms5611Reg {
     Reset,
     /// Digital pressure value
     D1,
@@ -59,7 +177,7 @@ enum Ms5611Reg {
 }
 
 impl Ms5611Reg {
-    fn addr(&self) -> u8 {
+    pub(crate) fn addr(&self) -> u8 {
         match *self {
             Ms5611Reg::Reset => 0x1e,
             Ms5611Reg::D1 => 0x40,
@@ -72,203 +190,515 @@ impl Ms5611Reg {
 }
 
 /// Output from the MS5611.
-#[derive(Debug)]
+///
+/// Values are fixed-point integers exactly as computed by the compensation,
+/// so no floating point support is required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Ms5611Sample {
-    /// Pressure measured in millibars.
-    pub pressure_mbar: f32,
-    /// Temperature in celsius.
-    pub temperature_c: f32,
+    /// Pressure measured in pascals (i.e. millibars * 100).
+    pub pressure_pa: i32,
+    /// Temperature in hundredths of a degree celsius.
+    pub temperature_centi_c: i32,
 }
 
-/// Factory calibrated data in device's ROM.
-#[derive(Debug)]
-struct Prom {
-    /// From datasheet, C1.
-    pub pressure_sensitivity: u16,
-    /// From datasheet, C2.
-    pub pressure_offset: u16,
-    /// From datasheet, C3.
-    pub temp_coef_pressure_sensitivity: u16,
-    /// From datasheet, C4.
-    pub temp_coef_pressure_offset: u16,
-    /// From datasheet, C5.
-    pub temp_ref: u16,
-    /// From datasheet, C6.
-    pub temp_coef_temp: u16,
+/// Unprocessed 24-bit ADC values of a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawSample {
+    /// Digital pressure value.
+    pub d1: u32,
+    /// Digital temperature value.
+    pub d2: u32,
+    /// Oversampling ratios the values were converted with.
+    pub sampling: SamplingConfig,
 }
 
-impl<I, E> Ms5611<I>
-where
-  I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
-{
+/// Compensated sample along with the inputs and intermediate terms of the
+/// compensation, for diagnostic logging. Names follow the datasheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticSample {
+    pub sample: Ms5611Sample,
+    pub raw: RawSample,
+    /// Difference between actual and reference temperature (dT).
+    pub dt: i64,
+    /// Offset at actual temperature (OFF), after second order compensation.
+    pub off: i64,
+    /// Sensitivity at actual temperature (SENS), after second order
+    /// compensation.
+    pub sens: i64,
+}
+
+/// Time span of a conversion, as read from a [`Clock`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversionSpan<I> {
+    /// Right after the conversion command was sent.
+    pub start: I,
+    /// Right before the result was read.
+    pub end: I,
+}
+
+/// Compensated sample along with the acquisition times of its conversions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimestampedSample<I> {
+    pub sample: Ms5611Sample,
+    /// Digital pressure (D1) conversion.
+    pub pressure: ConversionSpan<I>,
+    /// Digital temperature (D2) conversion.
+    pub temperature: ConversionSpan<I>,
+}
+
+#[cfg(feature = "float")]
+impl Ms5611Sample {
+    /// Pressure measured in millibars.
+    pub fn pressure_mbar(&self) -> f32 {
+        self.pressure_pa as f32/100.0
+    }
+
+    /// Temperature in celsius.
+    pub fn temperature_c(&self) -> f32 {
+        self.temperature_centi_c as f32/100.0
+    }
+}
+
+impl<I: I2c> Ms5611<I2cInterface<I>> {
 
     /// If i2c_addr is unspecified, 0x77 is used.
     /// The addr of the device is 0x77 if CSB is low / 0x76 if CSB is high.
-    pub fn new(mut i2c: I, i2c_addr: Option<u8>)
-            -> Result<Self, E> {
+    pub fn new(i2c: I, i2c_addr: Option<u8>)
+            -> Result<Self, Error<I::Error>> {
         let address = i2c_addr.unwrap_or(0x77);
 
-        let prom = Self::read_prom(&mut i2c, address)?;
+        Self::from_interface(I2cInterface::new(i2c, address))
+    }
 
-        let ms = Ms5611 {
-            i2c,
-            address: address,
-            prom
-        };
+    /// Like `new`, but trusts a previously read (and validated) `prom`
+    /// instead of reading it from the device. No bus transaction is issued.
+    pub fn with_prom(i2c: I, i2c_addr: Option<u8>, prom: Prom) -> Self {
+        let address = i2c_addr.unwrap_or(0x77);
+
+        Self::from_interface_with_prom(I2cInterface::new(i2c, address), prom)
+    }
+}
 
-        Ok(ms)
+impl<S: SpiDevice> Ms5611<SpiInterface<S>> {
+    /// The CSB pin of the device must be the chip select of `spi`.
+    pub fn new_spi(spi: S) -> Result<Self, Error<S::Error>> {
+        Self::from_interface(SpiInterface::new(spi))
+    }
+
+    /// Like `new_spi`, but trusts a previously read (and validated) `prom`
+    /// instead of reading it from the device. No bus transaction is issued.
+    pub fn with_prom_spi(spi: S, prom: Prom) -> Self {
+        Self::from_interface_with_prom(SpiInterface::new(spi), prom)
+    }
+}
+
+impl<B, E, V> Ms5611<B, V>
+where
+  B: Interface<Error = E>,
+  V: Variant,
+{
+
+    /// Creates the driver on top of an already configured transport.
+    ///
+    /// This is also how parts other than the MS5611 are constructed:
+    ///
+    /// ```ignore
+    /// let ms5607: Ms5611<_, variant::Ms5607> =
+    ///     Ms5611::from_interface(I2cInterface::new(i2c, 0x77))?;
+    /// ```
+    pub fn from_interface(mut bus: B) -> Result<Self, Error<E>> {
+        let prom = Self::read_prom(&mut bus)?;
+
+        Ok(Self::from_interface_with_prom(bus, prom))
+    }
+
+    /// Creates the driver on top of an already configured transport, using
+    /// calibration data restored from e.g. flash instead of reading the PROM.
+    ///
+    /// Panics if `prom` was created for a part with a different PROM layout,
+    /// see `Prom::new_for`.
+    pub fn from_interface_with_prom(bus: B, prom: Prom) -> Self {
+        assert_eq!(prom.layout(), V::PROM_LAYOUT,
+                   "calibration data of a different part");
+        Ms5611 {
+            bus,
+            prom,
+            sampling: Osr::Opt4096.into(),
+            temperature_ratio: 1,
+            last_d2: None,
+            pressures_since_temperature: 0,
+            retry: RetryPolicy::default(),
+            conversion: Conversion::Idle,
+            variant: PhantomData,
+        }
+    }
+
+    /// Cheap check that the cached calibration data belongs to the connected
+    /// device: only re-reads the PROM word holding the CRC and compares it.
+    ///
+    /// Returns `Error::InvalidProm` on mismatch.
+    pub fn verify_prom(&mut self) -> Result<(), Error<E>> {
+        let mut buf = [0u8; 2];
+        let crc_word = self.prom.layout().crc_word();
+        self.bus.write_read(Ms5611Reg::Prom.addr() + 2 * crc_word as u8, &mut buf)
+            .map_err(Error::Bus)?;
+
+        if BigEndian::read_u16(&buf) != self.prom.to_words()[crc_word] {
+            return Err(Error::InvalidProm);
+        }
+        Ok(())
     }
 
     /// Triggers a hardware reset of the device.
-    pub fn reset<D>(&mut self, delay: &mut D) -> Result<(), E>
-    where D: DelayMs<u8>
+    pub fn reset<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where D: DelayNs
     {
-        self.i2c.write(self.address, &[Ms5611Reg::Reset.addr()])?;
+        self.bus.write(Ms5611Reg::Reset.addr()).map_err(Error::Bus)?;
         // Haven't tested for the lower time bound necessary for the chip to
         // start functioning again. But, it does require some amount of sleep.
         delay.delay_ms(50);
         Ok(())
     }
 
-    fn read_prom(i2c: &mut I, address : u8) -> Result<Prom, E> {
-        let mut crc_check = 0u16;
-
-        // This is the CRC scheme in the MS5611 AN520 (Application Note)
-        fn crc_accumulate_byte(crc_check: &mut u16, byte: u8) {
-            *crc_check ^= byte as u16;
-            for _ in 0..8 {
-                if (*crc_check & 0x8000) > 0 {
-                    *crc_check = (*crc_check << 1) ^ 0x3000;
-                } else {
-                    *crc_check = *crc_check << 1;
-                }
-            }
+    fn read_prom(bus: &mut B) -> Result<Prom, Error<E>> {
+        let mut words = [0u16; 8];
+        let mut buf = [0u8; 2];
+        // Word 0 is reserved for manufacturer. We need it for the CRC.
+        for (i, word) in words.iter_mut().enumerate().take(V::PROM_LAYOUT.len()) {
+            bus.write_read(Ms5611Reg::Prom.addr() + 2 * i as u8, &mut buf)
+                .map_err(Error::Bus)?;
+            *word = BigEndian::read_u16(&buf);
         }
 
-        fn crc_accumulate_buf2(crc_check: &mut u16, buf: &[u8]) {
-            crc_accumulate_byte(crc_check,buf[0]);
-            crc_accumulate_byte(crc_check,buf[1]);
+        Prom::from_words_for::<V>(words).map_err(Error::widen)
+    }
+
+    /// Starts converting the digital pressure value (D1). The result can be
+    /// fetched with `read_adc` after `osr.conversion_time_ms()`.
+    ///
+    /// Returns `Error::UnsupportedOsr` for `Osr::Opt8192` on parts that don't
+    /// offer it.
+    pub fn start_pressure_conversion(&mut self, osr: Osr)
+            -> Result<(), Error<E>> {
+        check_osr::<V, E>(osr)?;
+        self.bus.write(Ms5611Reg::D1.addr() + osr.addr_modifier())
+            .map_err(Error::Bus)
+    }
+
+    /// Starts converting the digital temperature value (D2). The result can
+    /// be fetched with `read_adc` after `osr.conversion_time_ms()`.
+    pub fn start_temperature_conversion(&mut self, osr: Osr)
+            -> Result<(), Error<E>> {
+        check_osr::<V, E>(osr)?;
+        self.bus.write(Ms5611Reg::D2.addr() + osr.addr_modifier())
+            .map_err(Error::Bus)
+    }
+
+    /// Reads the 24-bit result of the last conversion.
+    ///
+    /// Returns `Error::AdcNotReady` if the conversion hasn't completed yet.
+    pub fn read_adc(&mut self) -> Result<u32, Error<E>> {
+        let mut buf = [0u8; 4];
+        self.bus.write_read(Ms5611Reg::AdcRead.addr(), &mut buf[1 .. 4])
+            .map_err(Error::Bus)?;
+        let adc = BigEndian::read_u32(&buf);
+        // If the conversion hasn't finished, the read is all 0s.
+        if adc == 0 {
+            return Err(Error::AdcNotReady);
         }
+        Ok(adc)
+    }
 
-        let mut buf: [u8; 2] = [0u8; 2];
-        // Address reserved for manufacturer. We need it for the CRC.
-        i2c.write_read(address, &[Ms5611Reg::Prom.addr()], &mut buf)?;
-        crc_accumulate_buf2(&mut crc_check, &buf);
+    /// Blocks for the conversion time of both oversampling ratios, see
+    /// `Osr::conversion_time_ms`. To avoid blocking, consider invoking this
+    /// function in a separate thread.
+    ///
+    /// `sampling` is either an `Osr` used for both conversions or a
+    /// `SamplingConfig` with separate ratios for pressure and temperature.
+    ///
+    /// Returns `Error::AdcNotReady` if a conversion result was read as 0,
+    /// which happens when the delay provider sleeps too short, and the
+    /// retries configured with `set_retry_policy` didn't help either.
+    pub fn read_sample<S, D>(&mut self, sampling: S, delay: &mut D)
+            -> Result<Ms5611Sample, Error<E>>
+    where S: Into<SamplingConfig>, D: DelayNs
+    {
+        let raw = self.read_raw(sampling, delay)?;
 
-        i2c.write_read(address, &[Ms5611Reg::Prom.addr() + 2], &mut buf)?;
-        let pressure_sensitivity = BigEndian::read_u16(&mut buf);
-        crc_accumulate_buf2(&mut crc_check, &buf);
+        Ok(self.prom.compensate_for::<V>(raw.d1, raw.d2))
+    }
 
-        i2c.write_read(address, &[Ms5611Reg::Prom.addr() + 4], &mut buf)?;
-        let pressure_offset = BigEndian::read_u16(&mut buf);
-        crc_accumulate_buf2(&mut crc_check, &buf);
+    /// Like `read_sample`, but also returns the raw ADC values and the
+    /// intermediate compensation terms.
+    pub fn read_sample_diagnostic<S, D>(&mut self, sampling: S, delay: &mut D)
+            -> Result<DiagnosticSample, Error<E>>
+    where S: Into<SamplingConfig>, D: DelayNs
+    {
+        let raw = self.read_raw(sampling, delay)?;
 
-        i2c.write_read(address, &[Ms5611Reg::Prom.addr() + 6], &mut buf)?;
-        let temp_coef_pressure_sensitivity = BigEndian::read_u16(&mut buf);
-        crc_accumulate_buf2(&mut crc_check, &buf);
+        Ok(self.prom.compensate_diagnostic_for::<V>(raw))
+    }
 
-        i2c.write_read(address, &[Ms5611Reg::Prom.addr() + 8], &mut buf)?;
-        let temp_coef_pressure_offset = BigEndian::read_u16(&mut buf);
-        crc_accumulate_buf2(&mut crc_check, &buf);
+    /// Like `read_sample`, but timestamps the start and end of both
+    /// conversions with `clock`. If a conversion is retried, the timestamps
+    /// refer to the last attempt.
+    pub fn read_sample_timestamped<S, D, C>(&mut self, sampling: S,
+                                            delay: &mut D, clock: &mut C)
+            -> Result<TimestampedSample<C::Instant>, Error<E>>
+    where S: Into<SamplingConfig>, D: DelayNs, C: Clock
+    {
+        let sampling = sampling.into();
 
-        i2c.write_read(address, &[Ms5611Reg::Prom.addr() + 10], &mut buf)?;
-        let temp_ref = BigEndian::read_u16(&mut buf);
-        crc_accumulate_buf2(&mut crc_check, &buf);
+        let (d1, pressure) = self.convert_timed(
+            Ms5611Reg::D1, sampling.pressure, delay, clock)?;
+        let (d2, temperature) = self.convert_timed(
+            Ms5611Reg::D2, sampling.temperature, delay, clock)?;
 
-        i2c.write_read(address, &[Ms5611Reg::Prom.addr() + 12], &mut buf)?;
-        let temp_coef_temp = BigEndian::read_u16(&mut buf);
-        crc_accumulate_buf2(&mut crc_check, &buf);
+        Ok(TimestampedSample {
+            sample: self.prom.compensate_for::<V>(d1, d2),
+            pressure,
+            temperature,
+        })
+    }
 
-        i2c.write_read(address, &[Ms5611Reg::Prom.addr() + 14], &mut buf)?;
-        // CRC is only last 4 bits
-        let crc = BigEndian::read_u16(&mut buf) & 0x000f;
-        crc_accumulate_byte(&mut crc_check, buf[0]);
-        crc_accumulate_byte(&mut crc_check, 0);
+    /// Performs the D1 and D2 conversions without compensating them.
+    /// Blocks like `read_sample`.
+    pub fn read_raw<S, D>(&mut self, sampling: S, delay: &mut D)
+            -> Result<RawSample, Error<E>>
+    where S: Into<SamplingConfig>, D: DelayNs
+    {
+        let sampling = sampling.into();
 
-        crc_check = crc_check >> 12;
+        // Raw digital pressure
+        let d1 = self.convert(Ms5611Reg::D1, sampling.pressure, delay)?;
+        // Raw digital temperature
+        let d2 = self.convert(Ms5611Reg::D2, sampling.temperature, delay)?;
 
-        if crc != crc_check {
-            panic!("PROM CRC did not match: {} != {}", crc, crc_check);
-        }
+        Ok(RawSample { d1, d2, sampling })
+    }
 
-        Ok(Prom {
-            pressure_sensitivity,
-            pressure_offset,
-            temp_coef_pressure_sensitivity,
-            temp_coef_pressure_offset,
-            temp_ref,
-            temp_coef_temp,
-        })
+    /// Runs a single conversion and reads its result, restarting it as
+    /// configured by the retry policy if the result reads as 0.
+    fn convert<D>(&mut self, reg: Ms5611Reg, osr: Osr, delay: &mut D)
+            -> Result<u32, Error<E>>
+    where D: DelayNs
+    {
+        self.convert_timed(reg, osr, delay, &mut NoClock)
+            .map(|(adc, _)| adc)
     }
 
-    /// Based on oversampling ratio, function may block between 1ms (OSR=256)
-    /// to 18ms (OSR=4096). To avoid blocking, consider invoking this function
-    /// in a separate thread.
-    pub fn read_sample<D>(&mut self, osr: Osr, delay: &mut D) -> Result<Ms5611Sample, E>
-    where D: DelayMs<u8>
+    /// Like `convert`, but also returns the time span of the conversion.
+    fn convert_timed<D, C>(&mut self, reg: Ms5611Reg, osr: Osr, delay: &mut D,
+                           clock: &mut C)
+            -> Result<(u32, ConversionSpan<C::Instant>), Error<E>>
+    where D: DelayNs, C: Clock
     {
-        // Note: Variable names aren't pretty, but they're consistent with the
-        // MS5611 datasheet.
-        let mut buf = [0u8; 4];
+        let mut attempt = 0;
+        loop {
+            check_osr::<V, E>(osr)?;
+            self.bus.write(reg.addr() + osr.addr_modifier())
+                .map_err(Error::Bus)?;
+            let start = clock.now();
+
+            // If we don't delay, the read is all 0s.
+            let extra_ms = if attempt == 0 {
+                0
+            } else {
+                self.retry.extra_delay_ms
+            };
+            delay.delay_ms(osr.conversion_time_ms() + extra_ms);
+            let end = clock.now();
+
+            match self.read_adc() {
+                Err(Error::AdcNotReady) if attempt < self.retry.retries =>
+                    attempt += 1,
+                res => return res.map(|adc| (adc, ConversionSpan { start, end })),
+            }
+        }
+    }
 
-        self.i2c.write(self.address, &[Ms5611Reg::D1.addr() + osr.addr_modifier()])?;
-        // If we don't delay, the read is all 0s.
-        delay.delay_ms(osr.get_delay());
-        self.i2c.write_read(self.address, &[Ms5611Reg::AdcRead.addr()], &mut buf[1 .. 4])?;
+    /// Factory calibration data read from the device.
+    pub fn prom(&self) -> &Prom {
+        &self.prom
+    }
 
-        // Raw digital pressure
-        let d1 = BigEndian::read_i32(&mut buf);
+    /// Computes the compensated sample from raw D1 and D2 values obtained
+    /// through `read_adc`.
+    pub fn compensate(&self, d1: u32, d2: u32) -> Ms5611Sample {
+        self.prom.compensate_for::<V>(d1, d2)
+    }
 
-        self.i2c.write(self.address, &[Ms5611Reg::D2.addr() + osr.addr_modifier()])?;
-        delay.delay_ms(osr.get_delay());
-        self.i2c.write_read(self.address, &[Ms5611Reg::AdcRead.addr()], &mut buf[1 .. 4])?;
+    /// Sets how blocking reads recover from a conversion result read as 0.
+    /// Retries are disabled by default. `poll` isn't affected, as it leaves
+    /// the timing to the caller.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
 
-        // Raw digital temperature
-        let d2 = BigEndian::read_i32(&mut buf) as i64;
+    /// Sets the oversampling ratio used for both the pressure and the
+    /// temperature conversions started by `poll` and `read_scheduled`.
+    /// Shorthand for `set_sampling(osr.into())`.
+    pub fn set_osr(&mut self, osr: Osr) {
+        self.set_sampling(osr.into());
+    }
 
-        // Temperature difference from reference
-        let dt = d2 - ((self.prom.temp_ref as i64) << 8);
+    /// Sets the oversampling ratios used for conversions started by `poll`
+    /// and `read_scheduled`. Takes effect with the next sample.
+    pub fn set_sampling(&mut self, sampling: SamplingConfig) {
+        self.sampling = sampling;
+    }
 
-        // Units: celcius * 100
-        let mut temperature: i32 = 2000 +
-            (((dt * (self.prom.temp_coef_temp as i64)) >> 23) as i32);
+    /// Oversampling ratios used by `poll` and `read_scheduled`.
+    pub fn sampling(&self) -> SamplingConfig {
+        self.sampling
+    }
 
-        let mut offset: i64 = ((self.prom.pressure_offset as i64) << 16)
-            + ((dt * (self.prom.temp_coef_pressure_offset as i64)) >> 7);
-        let mut sens: i64 = ((self.prom.pressure_sensitivity as i64) << 15)
-            + ((dt * (self.prom.temp_coef_pressure_sensitivity as i64)) >> 8);
+    /// Sets how many pressure conversions of `poll` and `read_scheduled`
+    /// share one temperature conversion (at least 1, the default). The other
+    /// samples are compensated with the last converted temperature, which
+    /// almost doubles the sample rate.
+    ///
+    /// The next sample converts the temperature.
+    pub fn set_temperature_ratio(&mut self, pressure_per_temperature: u16) {
+        self.temperature_ratio = pressure_per_temperature.max(1);
+        self.last_d2 = None;
+    }
 
-        let mut t2 = 0i32;
-        let mut off2 = 0i64;
-        let mut sens2 = 0i64;
+    /// Like `read_sample`, but follows the schedule set with `set_sampling`
+    /// and `set_temperature_ratio`, converting the temperature only when
+    /// it's due.
+    pub fn read_scheduled<D>(&mut self, delay: &mut D)
+            -> Result<Ms5611Sample, Error<E>>
+    where D: DelayNs
+    {
+        let d1 = self.convert(Ms5611Reg::D1, self.sampling.pressure, delay)?;
+        let d2 = match self.reusable_d2() {
+            Some(d2) => {
+                self.pressures_since_temperature += 1;
+                d2
+            },
+            None => {
+                let osr = self.sampling.temperature;
+                let d2 = self.convert(Ms5611Reg::D2, osr, delay)?;
+                self.store_d2(d2);
+                d2
+            },
+        };
 
-        //
-        // Second order temperature compensation
-        //
+        Ok(self.prom.compensate_for::<V>(d1, d2))
+    }
+
+    /// Last temperature conversion of the scheduler, unless a new one is due.
+    fn reusable_d2(&self) -> Option<u32> {
+        self.last_d2
+            .filter(|_| self.pressures_since_temperature < self.temperature_ratio)
+    }
 
-        // Low temperature (< 20C)
-        if temperature < 2000 {
-            t2 = ((dt * dt) >> 31) as i32;
-            off2 = ((5 * (temperature - 2000).pow(2)) >> 1) as i64;
-            sens2 = off2 >> 1;
+    /// Keeps a freshly converted D2 for the following pressure conversions.
+    fn store_d2(&mut self, d2: u32) {
+        self.last_d2 = Some(d2);
+        self.pressures_since_temperature = 1;
+    }
+
+    /// Advances the D1/D2 acquisition without blocking.
+    ///
+    /// `now` is a free-running millisecond clock (wrapping is handled). Each
+    /// call issues at most one bus command sequence and returns when the
+    /// next conversion will be ready, so it can be called from a super-loop
+    /// at any rate. Once the pressure and, if due according to
+    /// `set_temperature_ratio`, the temperature conversion completed, the
+    /// compensated sample is returned and the next call starts a new one.
+    ///
+    /// Don't mix with `read_sample` or the manual conversion API while a
+    /// sample is pending, as they share the device's single ADC.
+    pub fn poll(&mut self, now: u32) -> Result<Poll, Error<E>> {
+        Ok(match self.poll_raw(now)? {
+            Poll::Pending { ready_at } => Poll::Pending { ready_at },
+            Poll::Ready(raw) =>
+                Poll::Ready(self.prom.compensate_for::<V>(raw.d1, raw.d2)),
+        })
+    }
+
+    /// Like `poll`, but yields the raw ADC values instead of compensating
+    /// them. The temperature value may stem from an earlier sample, see
+    /// `set_temperature_ratio`.
+    pub fn poll_raw(&mut self, now: u32) -> Result<Poll<RawSample>, Error<E>> {
+        let res = self.poll_step(now);
+        if res.is_err() {
+            // Start from scratch on the next call.
+            self.conversion = Conversion::Idle;
         }
+        res
+    }
 
-        // Very low temperature (< -15)
-        if temperature < -1500 {
-            off2 += 7 * (temperature as i64 + 1500).pow(2);
-            sens2 += ((11 * (temperature as i64 + 1500).pow(2)) >> 1) as i64;
+    fn poll_step(&mut self, now: u32) -> Result<Poll<RawSample>, Error<E>> {
+        let sampling = self.sampling;
+
+        match self.conversion {
+            Conversion::Idle => {
+                let osr = self.sampling.pressure;
+                let ready_at = ready_at(now, osr);
+                self.start_pressure_conversion(osr)?;
+                self.conversion = Conversion::Pressure { ready_at };
+                Ok(Poll::Pending { ready_at })
+            },
+            Conversion::Pressure { ready_at: pending } => {
+                if !is_due(now, pending) {
+                    return Ok(Poll::Pending { ready_at: pending });
+                }
+                let d1 = self.read_adc()?;
+                if let Some(d2) = self.reusable_d2() {
+                    self.pressures_since_temperature += 1;
+                    self.conversion = Conversion::Idle;
+                    return Ok(Poll::Ready(RawSample { d1, d2, sampling }));
+                }
+
+                let osr = self.sampling.temperature;
+                let ready_at = ready_at(now, osr);
+                self.start_temperature_conversion(osr)?;
+                self.conversion = Conversion::Temperature { d1, ready_at };
+                Ok(Poll::Pending { ready_at })
+            },
+            Conversion::Temperature { d1, ready_at: pending } => {
+                if !is_due(now, pending) {
+                    return Ok(Poll::Pending { ready_at: pending });
+                }
+                let d2 = self.read_adc()?;
+                self.store_d2(d2);
+                self.conversion = Conversion::Idle;
+                Ok(Poll::Ready(RawSample { d1, d2, sampling }))
+            },
         }
+    }
+}
 
-        temperature -= t2;
-        offset -= off2;
-        sens -= sens2;
+/// Rejects oversampling ratios the part doesn't offer.
+pub(crate) fn check_osr<V: Variant, E>(osr: Osr) -> Result<(), Error<E>> {
+    if osr == Osr::Opt8192 && !V::SUPPORTS_OSR_8192 {
+        return Err(Error::UnsupportedOsr);
+    }
+    Ok(())
+}
 
-        // Units: mbar * 100
-        let pressure: i32 = (((((d1 as i64) * sens) >> 21) - offset) >> 15) as i32;
+/// Time at which a conversion started at `now` is ready. The clock may
+/// tick right after being read, so 1 ms is added for its granularity.
+fn ready_at(now: u32, osr: Osr) -> u32 {
+    now.wrapping_add(osr.conversion_time_ms() + 1)
+}
 
-        Ok(Ms5611Sample {
-            pressure_mbar: pressure as f32/100.0,
-            temperature_c: temperature as f32/100.0,
-        })
+/// Whether the wrapping millisecond clock `now` reached `deadline`.
+fn is_due(now: u32, deadline: u32) -> bool {
+    (now.wrapping_sub(deadline) as i32) >= 0
+}
+
+/// Median of a non-empty slice, the average of the middle values rounded
+/// half up for an even length.
+pub(crate) fn median(values: &mut [i64]) -> i64 {
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid]
+    } else {
+        (values[mid - 1] + values[mid] + 1).div_euclid(2)
     }
 }
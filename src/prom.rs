@@ -0,0 +1,224 @@
+//! Factory calibration data and the compensation math built on it.
+
+use core::convert::Infallible;
+
+use crate::variant::{self, PromLayout, SecondOrder, Variant};
+use crate::{DiagnosticSample, Error, Ms5611Sample, RawSample};
+
+/// Factory calibrated data in device's ROM.
+///
+/// Doesn't depend on a bus, so raw D1/D2 values can be post-processed offline
+/// with [`Prom::compensate`]. The raw PROM contents can be persisted with
+/// [`Prom::to_words`] and restored with [`Prom::from_words`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prom {
+    /// Raw PROM contents. Words 1 to 6 hold C1 to C6, the CRC is located as
+    /// described by `layout`. The remaining bits are reserved.
+    words: [u16; 8],
+    layout: PromLayout,
+}
+
+impl Prom {
+    /// Creates the calibration data from the coefficients C1 to C6, in
+    /// datasheet order. The CRC is computed, the reserved bits are left 0.
+    pub fn new(coefficients: [u16; 6]) -> Prom {
+        Self::new_for::<variant::Ms5611>(coefficients)
+    }
+
+    /// Like `new`, with the PROM layout of any part of the sensor family.
+    pub fn new_for<V: Variant>(coefficients: [u16; 6]) -> Prom {
+        let layout = V::PROM_LAYOUT;
+        let mut words = [0u16; 8];
+        words[1 .. 7].copy_from_slice(&coefficients);
+        words[layout.crc_word()] |=
+            (crc4(&words, layout) as u16) << layout.crc_shift();
+        Prom { words, layout }
+    }
+
+    /// Validates and decodes the eight 16-bit words read from the PROM of an
+    /// MS5611.
+    ///
+    /// Fails with `Error::Crc` if the CRC nibble doesn't match and with
+    /// `Error::InvalidProm` if the coefficients are all 0s or all 1s.
+    pub fn from_words(words: [u16; 8]) -> Result<Prom, Error<Infallible>> {
+        Self::from_words_for::<variant::Ms5611>(words)
+    }
+
+    /// Like `from_words`, with the PROM layout of any part of the sensor
+    /// family. For parts with a seven word PROM, word 7 must be 0, otherwise
+    /// `Error::InvalidProm` is returned.
+    pub fn from_words_for<V: Variant>(words: [u16; 8])
+            -> Result<Prom, Error<Infallible>> {
+        let prom = Prom { words, layout: V::PROM_LAYOUT };
+
+        let crc_check = crc4(&words, prom.layout);
+        if prom.crc() != crc_check {
+            return Err(Error::Crc {
+                expected: prom.crc(),
+                computed: crc_check,
+            });
+        }
+
+        // A floating or shorted bus reads back all 0s (or all 1s), for which
+        // the CRC happens to check out. Word 7 of a seven word PROM isn't
+        // covered by the CRC, but doesn't exist on the device either.
+        if prom.is_blank()
+            || (prom.layout == PromLayout::CrcInWord0 && words[7] != 0) {
+            return Err(Error::InvalidProm);
+        }
+
+        Ok(prom)
+    }
+
+    /// Raw PROM contents, as accepted by `from_words`.
+    pub fn to_words(&self) -> [u16; 8] {
+        self.words
+    }
+
+    /// Location of the CRC.
+    pub fn layout(&self) -> PromLayout {
+        self.layout
+    }
+
+    /// From datasheet, C1.
+    pub fn pressure_sensitivity(&self) -> u16 {
+        self.words[1]
+    }
+
+    /// From datasheet, C2.
+    pub fn pressure_offset(&self) -> u16 {
+        self.words[2]
+    }
+
+    /// From datasheet, C3.
+    pub fn temp_coef_pressure_sensitivity(&self) -> u16 {
+        self.words[3]
+    }
+
+    /// From datasheet, C4.
+    pub fn temp_coef_pressure_offset(&self) -> u16 {
+        self.words[4]
+    }
+
+    /// From datasheet, C5.
+    pub fn temp_ref(&self) -> u16 {
+        self.words[5]
+    }
+
+    /// From datasheet, C6.
+    pub fn temp_coef_temp(&self) -> u16 {
+        self.words[6]
+    }
+
+    /// CRC stored in the PROM.
+    pub fn crc(&self) -> u8 {
+        let layout = self.layout;
+        ((self.words[layout.crc_word()] >> layout.crc_shift()) & 0x000f) as u8
+    }
+
+    fn is_blank(&self) -> bool {
+        let coefs = &self.words[1 .. 7];
+        coefs.iter().all(|&c| c == 0) || coefs.iter().all(|&c| c == 0xffff)
+    }
+
+    /// Computes the first and second order compensated sample from the raw
+    /// digital pressure (D1) and temperature (D2) values of an MS5611.
+    pub fn compensate(&self, d1: u32, d2: u32) -> Ms5611Sample {
+        self.compensate_for::<variant::Ms5611>(d1, d2)
+    }
+
+    /// Like `compensate`, for any part of the sensor family.
+    pub fn compensate_for<V: Variant>(&self, d1: u32, d2: u32) -> Ms5611Sample {
+        self.compensate_terms::<V>(d1, d2).0
+    }
+
+    /// Like `compensate`, but also returns the intermediate terms.
+    pub fn compensate_diagnostic(&self, raw: RawSample) -> DiagnosticSample {
+        self.compensate_diagnostic_for::<variant::Ms5611>(raw)
+    }
+
+    /// Like `compensate_diagnostic`, for any part of the sensor family.
+    pub fn compensate_diagnostic_for<V: Variant>(&self, raw: RawSample)
+            -> DiagnosticSample {
+        let (sample, dt, off, sens) =
+            self.compensate_terms::<V>(raw.d1, raw.d2);
+
+        DiagnosticSample { sample, raw, dt, off, sens }
+    }
+
+    /// Returns the sample along with dT, OFF and SENS.
+    fn compensate_terms<V: Variant>(&self, d1: u32, d2: u32)
+            -> (Ms5611Sample, i64, i64, i64) {
+
+        // Note: Variable names aren't pretty, but they're consistent with the
+        // MS5611 datasheet.
+
+        // Temperature difference from reference
+        let dt = d2 as i64 - ((self.temp_ref() as i64) << 8);
+
+        // Units: celcius * 100
+        let mut temperature: i32 = 2000 +
+            (((dt * (self.temp_coef_temp() as i64)) >> 23) as i32);
+
+        let mut offset: i64 = ((self.pressure_offset() as i64) << V::OFF_C2_SHIFT)
+            + ((dt * (self.temp_coef_pressure_offset() as i64)) >> V::OFF_C4_SHIFT);
+        let mut sens: i64 = ((self.pressure_sensitivity() as i64) << V::SENS_C1_SHIFT)
+            + ((dt * (self.temp_coef_pressure_sensitivity() as i64)) >> V::SENS_C3_SHIFT);
+
+        //
+        // Second order temperature compensation
+        //
+
+        let SecondOrder { t2, off2, sens2 } = V::second_order(temperature, dt);
+
+        temperature -= t2;
+        offset -= off2;
+        sens -= sens2;
+
+        let pressure: i32 = (((((d1 as i64) * sens) >> 21) - offset)
+            >> V::PRESSURE_SHIFT) as i32;
+
+        let sample = Ms5611Sample {
+            pressure_pa: pressure * V::PA_PER_LSB,
+            temperature_centi_c: temperature,
+        };
+
+        (sample, dt, offset, sens)
+    }
+}
+
+/// This is the CRC scheme in the MS5611 AN520 (Application Note).
+///
+/// The CRC nibble itself is excluded. In the eight word layout the reserved
+/// bits next to it are excluded as well, i.e. the whole low byte of word 7.
+/// Word 7 of seven word PROMs doesn't exist and is treated as 0.
+fn crc4(words: &[u16; 8], layout: PromLayout) -> u8 {
+    let mut crc_check = 0u16;
+
+    fn crc_accumulate_byte(crc_check: &mut u16, byte: u8) {
+        *crc_check ^= byte as u16;
+        for _ in 0..8 {
+            if (*crc_check & 0x8000) > 0 {
+                *crc_check = (*crc_check << 1) ^ 0x3000;
+            } else {
+                *crc_check <<= 1;
+            }
+        }
+    }
+
+    let mut words = *words;
+    match layout {
+        PromLayout::CrcInWord7 => words[7] &= 0xff00,
+        PromLayout::CrcInWord0 => {
+            words[0] &= 0x0fff;
+            words[7] = 0;
+        },
+    }
+
+    for &word in words.iter() {
+        crc_accumulate_byte(&mut crc_check, (word >> 8) as u8);
+        crc_accumulate_byte(&mut crc_check, word as u8);
+    }
+
+    (crc_check >> 12) as u8
+}
@@ -0,0 +1,178 @@
+//! Continuous acquisition with averaging in the driver.
+//!
+//! [`ContinuousSampler`] keeps the sensor converting back to back and
+//! remembers the most recent raw samples in a fixed-size ring buffer, from
+//! which smoothed samples are computed on demand. Averages and medians are
+//! taken over the raw D1 and D2 values, which are then compensated like a
+//! single sample.
+
+use crate::variant::{self, Variant};
+use crate::{median, Error, Interface, Ms5611, Ms5611Sample, Poll, RawSample};
+
+/// Runs back-to-back conversions and smooths the results.
+///
+/// `N` is the capacity of the ring buffer, i.e. the window of
+/// [`moving_average`](Self::moving_average) and [`median`](Self::median).
+/// Independently of it, [`poll`](Self::poll) yields the average of every
+/// `decimation` consecutive samples, lowering the output rate accordingly.
+///
+/// The conversions follow the driver's sampling configuration, see
+/// [`Ms5611::set_sampling`] and [`Ms5611::set_temperature_ratio`].
+pub struct ContinuousSampler<B, const N: usize, V = variant::Ms5611> {
+    ms5611: Ms5611<B, V>,
+    /// Ring buffer, valid up to `len`.
+    buffer: [RawSample; N],
+    /// Index the next sample is stored at.
+    next: usize,
+    len: usize,
+    decimation: u16,
+    /// Sums of the samples since the last decimated output.
+    d1_sum: u64,
+    d2_sum: u64,
+    decimation_count: u16,
+}
+
+impl<B, E, V, const N: usize> ContinuousSampler<B, N, V>
+where
+  B: Interface<Error = E>,
+  V: Variant,
+{
+    /// Wraps the driver. `decimation` (at least 1) samples are averaged for
+    /// every output of `poll`.
+    ///
+    /// Panics if `N` is 0.
+    pub fn new(ms5611: Ms5611<B, V>, decimation: u16) -> Self {
+        assert!(N > 0, "ring buffer without capacity");
+        let empty = RawSample {
+            d1: 0,
+            d2: 0,
+            sampling: ms5611.sampling(),
+        };
+
+        ContinuousSampler {
+            ms5611,
+            buffer: [empty; N],
+            next: 0,
+            len: 0,
+            decimation: decimation.max(1),
+            d1_sum: 0,
+            d2_sum: 0,
+            decimation_count: 0,
+        }
+    }
+
+    /// Advances the acquisition without blocking, see [`Ms5611::poll`].
+    ///
+    /// Whenever a sample completes, the next one is started right away.
+    /// Once `decimation` samples were acquired since the last output, the
+    /// decimated sample is returned instead, and the next sample starts with
+    /// the following call.
+    pub fn poll(&mut self, now: u32) -> Result<Poll, Error<E>> {
+        loop {
+            let raw = match self.ms5611.poll_raw(now)? {
+                Poll::Pending { ready_at } => return Ok(Poll::Pending { ready_at }),
+                Poll::Ready(raw) => raw,
+            };
+            self.push(raw);
+
+            if self.decimation_count == self.decimation {
+                let count = self.decimation as u64;
+                let d1 = rounded_div(self.d1_sum, count);
+                let d2 = rounded_div(self.d2_sum, count);
+                self.d1_sum = 0;
+                self.d2_sum = 0;
+                self.decimation_count = 0;
+
+                return Ok(Poll::Ready(self.ms5611.compensate(d1, d2)));
+            }
+        }
+    }
+
+    fn push(&mut self, raw: RawSample) {
+        self.buffer[self.next] = raw;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+
+        self.d1_sum += raw.d1 as u64;
+        self.d2_sum += raw.d2 as u64;
+        self.decimation_count += 1;
+    }
+
+    /// Raw samples in the ring buffer, oldest first.
+    pub fn raw_samples(&self) -> impl Iterator<Item = RawSample> + '_ {
+        let start = (self.next + N - self.len) % N;
+        (0 .. self.len).map(move |i| self.buffer[(start + i) % N])
+    }
+
+    /// Most recent raw sample.
+    pub fn latest(&self) -> Option<RawSample> {
+        if self.len == 0 {
+            return None;
+        }
+        Some(self.buffer[(self.next + N - 1) % N])
+    }
+
+    /// Average of the samples in the ring buffer.
+    pub fn moving_average(&self) -> Option<Ms5611Sample> {
+        if self.len == 0 {
+            return None;
+        }
+        let (d1_sum, d2_sum) = self.raw_samples()
+            .fold((0u64, 0u64), |(d1, d2), raw| {
+                (d1 + raw.d1 as u64, d2 + raw.d2 as u64)
+            });
+        let count = self.len as u64;
+
+        Some(self.ms5611.compensate(rounded_div(d1_sum, count),
+                                    rounded_div(d2_sum, count)))
+    }
+
+    /// Median of the samples in the ring buffer, taken separately for D1 and
+    /// D2. Unlike the average, it isn't dragged off by single spikes.
+    pub fn median(&self) -> Option<Ms5611Sample> {
+        if self.len == 0 {
+            return None;
+        }
+        let mut d1 = [0i64; N];
+        let mut d2 = [0i64; N];
+        for (i, raw) in self.raw_samples().enumerate() {
+            d1[i] = raw.d1 as i64;
+            d2[i] = raw.d2 as i64;
+        }
+
+        Some(self.ms5611.compensate(median(&mut d1[.. self.len]) as u32,
+                                    median(&mut d2[.. self.len]) as u32))
+    }
+
+    /// Number of samples in the ring buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Forgets all samples, e.g. after a gap in the acquisition.
+    pub fn clear(&mut self) {
+        self.next = 0;
+        self.len = 0;
+        self.d1_sum = 0;
+        self.d2_sum = 0;
+        self.decimation_count = 0;
+    }
+
+    /// Underlying driver. Don't start conversions on it while sampling.
+    pub fn ms5611(&mut self) -> &mut Ms5611<B, V> {
+        &mut self.ms5611
+    }
+
+    /// Releases the underlying driver.
+    pub fn release(self) -> Ms5611<B, V> {
+        self.ms5611
+    }
+}
+
+fn rounded_div(sum: u64, count: u64) -> u32 {
+    ((sum + count / 2) / count) as u32
+}
@@ -0,0 +1,166 @@
+//! Simulated MS5611 for host tests.
+//!
+//! [`SimMs5611`] implements the embedded-hal `I2c` trait and answers the
+//! command set like the real device, so the driver can be exercised end to
+//! end without hardware. Conversions complete instantly.
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+
+use crate::{Ms5611Reg, Prom};
+
+/// Simulated MS5611 on an I2C bus.
+///
+/// The digital pressure and temperature values (D1/D2) are derived from the
+/// configured pressure and temperature by inverting the compensation with
+/// the PROM's coefficients.
+#[derive(Debug, Clone)]
+pub struct SimMs5611 {
+    address: u8,
+    words: [u16; 8],
+    pressure_pa: i32,
+    temperature_centi_c: i32,
+    /// Last command byte written.
+    command: Option<u8>,
+    /// Result of the last conversion. Reading the ADC clears it.
+    adc: u32,
+    /// Number of upcoming ADC reads that return 0 as if read too early.
+    premature_reads: u32,
+}
+
+impl SimMs5611 {
+    /// Simulates a device at `address` with the calibration data of the
+    /// datasheet's worked example, at 1013.25 mbar and 20 °C.
+    pub fn new(address: u8) -> Self {
+        Self::with_prom(address,
+                        &Prom::new([40127, 36924, 23317, 23282, 33464, 28312]))
+    }
+
+    /// Simulates a device with the given calibration data.
+    pub fn with_prom(address: u8, prom: &Prom) -> Self {
+        Self::with_prom_words(address, prom.to_words())
+    }
+
+    /// Simulates a device with raw PROM contents, which may have an invalid
+    /// CRC.
+    pub fn with_prom_words(address: u8, words: [u16; 8]) -> Self {
+        SimMs5611 {
+            address,
+            words,
+            pressure_pa: 101_325,
+            temperature_centi_c: 2000,
+            command: None,
+            adc: 0,
+            premature_reads: 0,
+        }
+    }
+
+    /// Sets the conditions the following conversions reflect.
+    pub fn set_conditions(&mut self, pressure_pa: i32, temperature_centi_c: i32) {
+        self.pressure_pa = pressure_pa;
+        self.temperature_centi_c = temperature_centi_c;
+    }
+
+    /// Makes the next `reads` ADC reads return 0, as when the conversion
+    /// hasn't completed yet. Each of them discards the conversion result.
+    pub fn set_premature_reads(&mut self, reads: u32) {
+        self.premature_reads = reads;
+    }
+
+    /// Raw D1 and D2 values the device converts under the current conditions.
+    pub fn raw_values(&self) -> (u32, u32) {
+        // The coefficients are trusted even if the CRC doesn't check out.
+        let mut coefficients = [0u16; 6];
+        coefficients.copy_from_slice(&self.words[1 .. 7]);
+        let prom = Prom::new(coefficients);
+
+        // Temperature only depends on D2, and rises with it.
+        let d2 = search(|d2| {
+            prom.compensate(1, d2).temperature_centi_c >= self.temperature_centi_c
+        });
+        // With D2 fixed, pressure rises with D1.
+        let d1 = search(|d1| {
+            prom.compensate(d1, d2).pressure_pa >= self.pressure_pa
+        });
+
+        (d1, d2)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), ErrorKind> {
+        let cmd = *bytes.first().ok_or(ErrorKind::Other)?;
+        let d1 = Ms5611Reg::D1.addr();
+        let d2 = Ms5611Reg::D2.addr();
+
+        match cmd {
+            _ if cmd == Ms5611Reg::Reset.addr() => self.adc = 0,
+            _ if cmd == Ms5611Reg::AdcRead.addr() => {},
+            _ if (d1 ..= d1 + 8).contains(&cmd) && cmd % 2 == 0 =>
+                self.adc = self.raw_values().0,
+            _ if (d2 ..= d2 + 8).contains(&cmd) && cmd % 2 == 0 =>
+                self.adc = self.raw_values().1,
+            _ if (Ms5611Reg::Prom.addr() ..= Ms5611Reg::Prom.addr() + 14)
+                    .contains(&cmd) && cmd % 2 == 0 => {},
+            _ => return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)),
+        }
+
+        self.command = Some(cmd);
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<(), ErrorKind> {
+        let cmd = self.command.ok_or(ErrorKind::Other)?;
+
+        if cmd == Ms5611Reg::AdcRead.addr() {
+            if self.premature_reads > 0 {
+                self.premature_reads -= 1;
+                self.adc = 0;
+            }
+            let adc = self.adc.to_be_bytes();
+            let n = buf.len().min(3);
+            buf[.. n].copy_from_slice(&adc[1 .. 1 + n]);
+            self.adc = 0;
+        } else if cmd >= Ms5611Reg::Prom.addr() {
+            let word = self.words[((cmd - Ms5611Reg::Prom.addr()) / 2) as usize];
+            let word = word.to_be_bytes();
+            let n = buf.len().min(2);
+            buf[.. n].copy_from_slice(&word[.. n]);
+        } else {
+            return Err(ErrorKind::Other);
+        }
+        Ok(())
+    }
+}
+
+/// Smallest 24-bit value for which `reached` holds, assuming it's monotonic.
+fn search<F: Fn(u32) -> bool>(reached: F) -> u32 {
+    let (mut low, mut high) = (1u32, (1 << 24) - 1);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if reached(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    low
+}
+
+impl ErrorType for SimMs5611 {
+    type Error = ErrorKind;
+}
+
+impl I2c for SimMs5611 {
+    fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>])
+            -> Result<(), ErrorKind> {
+        if address != self.address {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+
+        for op in operations {
+            match op {
+                Operation::Write(bytes) => self.write(bytes)?,
+                Operation::Read(buf) => self.read(buf)?,
+            }
+        }
+        Ok(())
+    }
+}
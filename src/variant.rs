@@ -0,0 +1,281 @@
+//! Sensors of the MEAS family sharing the MS5611 command set.
+//!
+//! The parts differ in the shifts of the first order compensation, their
+//! second order temperature compensation, the location of the PROM CRC and
+//! whether they offer OSR 8192. The driver and
+//! [`Prom`](crate::Prom) are generic over a [`Variant`] marker type, which
+//! defaults to [`Ms5611`]. Other parts can be supported by implementing
+//! [`Variant`] for a new marker type.
+
+/// Second order temperature compensation terms, named as in the datasheets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SecondOrder {
+    /// Subtracted from TEMP.
+    pub t2: i32,
+    /// Subtracted from OFF.
+    pub off2: i64,
+    /// Subtracted from SENS.
+    pub sens2: i64,
+}
+
+/// Location of the CRC in the PROM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromLayout {
+    /// Eight words, the CRC is in the lowest 4 bits of word 7.
+    CrcInWord7,
+    /// Seven words, the CRC is in the highest 4 bits of word 0. Word 7 doesn't
+    /// exist and is treated as 0.
+    CrcInWord0,
+}
+
+impl PromLayout {
+    /// Number of words stored in the PROM.
+    pub(crate) fn len(self) -> usize {
+        match self {
+            PromLayout::CrcInWord7 => 8,
+            PromLayout::CrcInWord0 => 7,
+        }
+    }
+
+    /// Index of the word holding the CRC.
+    pub(crate) fn crc_word(self) -> usize {
+        match self {
+            PromLayout::CrcInWord7 => 7,
+            PromLayout::CrcInWord0 => 0,
+        }
+    }
+
+    /// Position of the CRC nibble within its word.
+    pub(crate) fn crc_shift(self) -> u32 {
+        match self {
+            PromLayout::CrcInWord7 => 0,
+            PromLayout::CrcInWord0 => 12,
+        }
+    }
+}
+
+/// Compensation coefficients and capabilities of a sensor part.
+///
+/// The first order compensation of all parts is
+///
+/// ```text
+/// OFF  = C2 << OFF_C2_SHIFT + (C4 * dT) >> OFF_C4_SHIFT
+/// SENS = C1 << SENS_C1_SHIFT + (C3 * dT) >> SENS_C3_SHIFT
+/// P    = ((D1 * SENS) >> 21 - OFF) >> PRESSURE_SHIFT
+/// ```
+///
+/// with `P` in units of `PA_PER_LSB` pascals.
+pub trait Variant {
+    const OFF_C2_SHIFT: u32;
+    const OFF_C4_SHIFT: u32;
+    const SENS_C1_SHIFT: u32;
+    const SENS_C3_SHIFT: u32;
+    const PRESSURE_SHIFT: u32;
+    const PA_PER_LSB: i32;
+
+    const PROM_LAYOUT: PromLayout = PromLayout::CrcInWord7;
+    /// Whether the part offers [`Osr::Opt8192`](crate::Osr::Opt8192).
+    const SUPPORTS_OSR_8192: bool = false;
+
+    /// Second order compensation for the first order `temperature` (celsius
+    /// * 100) and temperature difference `dt`.
+    fn second_order(temperature: i32, dt: i64) -> SecondOrder;
+}
+
+/// MS5611-01BA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ms5611;
+
+impl Variant for Ms5611 {
+    const OFF_C2_SHIFT: u32 = 16;
+    const OFF_C4_SHIFT: u32 = 7;
+    const SENS_C1_SHIFT: u32 = 15;
+    const SENS_C3_SHIFT: u32 = 8;
+    const PRESSURE_SHIFT: u32 = 15;
+    const PA_PER_LSB: i32 = 1;
+
+    fn second_order(temperature: i32, dt: i64) -> SecondOrder {
+        let mut terms = SecondOrder::default();
+        let temperature = temperature as i64;
+
+        // Low temperature (< 20C)
+        if temperature < 2000 {
+            terms.t2 = ((dt * dt) >> 31) as i32;
+            terms.off2 = (5 * (temperature - 2000).pow(2)) >> 1;
+            terms.sens2 = terms.off2 >> 1;
+        }
+
+        // Very low temperature (< -15)
+        if temperature < -1500 {
+            terms.off2 += 7 * (temperature + 1500).pow(2);
+            terms.sens2 += (11 * (temperature + 1500).pow(2)) >> 1;
+        }
+
+        terms
+    }
+}
+
+/// MS5607-02BA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ms5607;
+
+impl Variant for Ms5607 {
+    const OFF_C2_SHIFT: u32 = 17;
+    const OFF_C4_SHIFT: u32 = 6;
+    const SENS_C1_SHIFT: u32 = 16;
+    const SENS_C3_SHIFT: u32 = 7;
+    const PRESSURE_SHIFT: u32 = 15;
+    const PA_PER_LSB: i32 = 1;
+
+    fn second_order(temperature: i32, dt: i64) -> SecondOrder {
+        let mut terms = SecondOrder::default();
+        let temperature = temperature as i64;
+
+        if temperature < 2000 {
+            terms.t2 = ((dt * dt) >> 31) as i32;
+            terms.off2 = (61 * (temperature - 2000).pow(2)) >> 4;
+            terms.sens2 = 2 * (temperature - 2000).pow(2);
+        }
+
+        if temperature < -1500 {
+            terms.off2 += 15 * (temperature + 1500).pow(2);
+            terms.sens2 += 8 * (temperature + 1500).pow(2);
+        }
+
+        terms
+    }
+}
+
+/// MS5637-02BA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ms5637;
+
+impl Variant for Ms5637 {
+    const OFF_C2_SHIFT: u32 = 17;
+    const OFF_C4_SHIFT: u32 = 6;
+    const SENS_C1_SHIFT: u32 = 16;
+    const SENS_C3_SHIFT: u32 = 7;
+    const PRESSURE_SHIFT: u32 = 15;
+    const PA_PER_LSB: i32 = 1;
+
+    const PROM_LAYOUT: PromLayout = PromLayout::CrcInWord0;
+    const SUPPORTS_OSR_8192: bool = true;
+
+    fn second_order(temperature: i32, dt: i64) -> SecondOrder {
+        let mut terms = SecondOrder::default();
+        let temperature = temperature as i64;
+
+        if temperature < 2000 {
+            terms.t2 = ((3 * dt * dt) >> 33) as i32;
+            terms.off2 = (61 * (temperature - 2000).pow(2)) >> 4;
+            terms.sens2 = (29 * (temperature - 2000).pow(2)) >> 4;
+
+            if temperature < -1500 {
+                terms.off2 += 17 * (temperature + 1500).pow(2);
+                terms.sens2 += 9 * (temperature + 1500).pow(2);
+            }
+        } else {
+            terms.t2 = ((5 * dt * dt) >> 38) as i32;
+        }
+
+        terms
+    }
+}
+
+/// MS5803-01BA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ms5803Ba01;
+
+impl Variant for Ms5803Ba01 {
+    const OFF_C2_SHIFT: u32 = 16;
+    const OFF_C4_SHIFT: u32 = 7;
+    const SENS_C1_SHIFT: u32 = 15;
+    const SENS_C3_SHIFT: u32 = 8;
+    const PRESSURE_SHIFT: u32 = 15;
+    const PA_PER_LSB: i32 = 1;
+
+    fn second_order(temperature: i32, dt: i64) -> SecondOrder {
+        let mut terms = SecondOrder::default();
+        let temperature = temperature as i64;
+
+        if temperature < 2000 {
+            terms.t2 = ((dt * dt) >> 31) as i32;
+            terms.off2 = 3 * (temperature - 2000).pow(2);
+            terms.sens2 = (7 * (temperature - 2000).pow(2)) >> 3;
+
+            if temperature < -1500 {
+                terms.sens2 += 2 * (temperature + 1500).pow(2);
+            }
+        } else if temperature >= 4500 {
+            terms.sens2 -= (temperature - 4500).pow(2) >> 3;
+        }
+
+        terms
+    }
+}
+
+/// MS5837-30BA. Resolves pressure in steps of 10 Pa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ms5837Ba30;
+
+impl Variant for Ms5837Ba30 {
+    const OFF_C2_SHIFT: u32 = 16;
+    const OFF_C4_SHIFT: u32 = 7;
+    const SENS_C1_SHIFT: u32 = 15;
+    const SENS_C3_SHIFT: u32 = 8;
+    const PRESSURE_SHIFT: u32 = 13;
+    const PA_PER_LSB: i32 = 10;
+
+    const PROM_LAYOUT: PromLayout = PromLayout::CrcInWord0;
+    const SUPPORTS_OSR_8192: bool = true;
+
+    fn second_order(temperature: i32, dt: i64) -> SecondOrder {
+        let mut terms = SecondOrder::default();
+        let temperature = temperature as i64;
+
+        if temperature < 2000 {
+            terms.t2 = ((3 * dt * dt) >> 33) as i32;
+            terms.off2 = (3 * (temperature - 2000).pow(2)) >> 1;
+            terms.sens2 = (5 * (temperature - 2000).pow(2)) >> 3;
+
+            if temperature < -1500 {
+                terms.off2 += 7 * (temperature + 1500).pow(2);
+                terms.sens2 += 4 * (temperature + 1500).pow(2);
+            }
+        } else {
+            terms.t2 = ((2 * dt * dt) >> 37) as i32;
+            terms.off2 = (temperature - 2000).pow(2) >> 4;
+        }
+
+        terms
+    }
+}
+
+/// MS5837-02BA.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ms5837Ba02;
+
+impl Variant for Ms5837Ba02 {
+    const OFF_C2_SHIFT: u32 = 17;
+    const OFF_C4_SHIFT: u32 = 6;
+    const SENS_C1_SHIFT: u32 = 16;
+    const SENS_C3_SHIFT: u32 = 7;
+    const PRESSURE_SHIFT: u32 = 15;
+    const PA_PER_LSB: i32 = 1;
+
+    const PROM_LAYOUT: PromLayout = PromLayout::CrcInWord0;
+    const SUPPORTS_OSR_8192: bool = true;
+
+    fn second_order(temperature: i32, dt: i64) -> SecondOrder {
+        let mut terms = SecondOrder::default();
+        let temperature = temperature as i64;
+
+        if temperature < 2000 {
+            terms.t2 = ((11 * dt * dt) >> 35) as i32;
+            terms.off2 = (31 * (temperature - 2000).pow(2)) >> 3;
+            terms.sens2 = (63 * (temperature - 2000).pow(2)) >> 5;
+        }
+
+        terms
+    }
+}
@@ -0,0 +1,148 @@
+//! Vertical speed estimation from successive samples.
+
+use crate::altitude;
+use crate::Ms5611Sample;
+
+/// Estimates the filtered vertical speed from timestamped samples.
+///
+/// Uses an alpha-beta filter on the pressure altitude, with the
+/// Benedict–Bordner choice of `beta = alpha² / (2 - alpha)` for the gains. The
+/// time constant trades responsiveness for noise: ~1 s suits paragliding,
+/// shorter values react faster to thermals but jitter more.
+#[derive(Debug, Clone)]
+pub struct Variometer {
+    /// In seconds.
+    time_constant: f32,
+    state: Option<State>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct State {
+    /// Timestamp of the last sample, in milliseconds.
+    timestamp_ms: u32,
+    /// Filtered altitude, in metres.
+    altitude_m: f32,
+    /// Filtered vertical speed, in m/s.
+    vertical_speed: f32,
+}
+
+impl Variometer {
+    /// `time_constant` is in seconds.
+    pub fn new(time_constant: f32) -> Self {
+        Variometer {
+            time_constant,
+            state: None,
+        }
+    }
+
+    /// Feeds a sample captured at `timestamp_ms` (free-running, wrapping
+    /// millisecond clock) and returns the vertical speed in m/s.
+    ///
+    /// The first sample only initializes the filter and yields 0. Samples
+    /// without time progress are ignored.
+    pub fn update(&mut self, timestamp_ms: u32, sample: &Ms5611Sample) -> f32 {
+        let altitude_m = altitude::pressure_altitude(sample.pressure_pa as f32);
+
+        let state = match self.state {
+            None => {
+                self.state = Some(State {
+                    timestamp_ms,
+                    altitude_m,
+                    vertical_speed: 0.0,
+                });
+                return 0.0;
+            },
+            Some(ref mut state) => state,
+        };
+
+        let dt_ms = timestamp_ms.wrapping_sub(state.timestamp_ms);
+        if dt_ms == 0 {
+            return state.vertical_speed;
+        }
+        let dt = dt_ms as f32 / 1000.0;
+
+        let alpha = 1.0 - libm::expf(-dt / self.time_constant);
+        let beta = alpha * alpha / (2.0 - alpha);
+
+        let predicted = state.altitude_m + state.vertical_speed * dt;
+        let residual = altitude_m - predicted;
+        state.altitude_m = predicted + alpha * residual;
+        state.vertical_speed += beta / dt * residual;
+        state.timestamp_ms = timestamp_ms;
+
+        state.vertical_speed
+    }
+
+    /// Last estimated vertical speed in m/s, 0 before the first sample.
+    pub fn vertical_speed(&self) -> f32 {
+        self.state.map_or(0.0, |s| s.vertical_speed)
+    }
+
+    /// Last filtered pressure altitude in metres, if any sample was fed.
+    pub fn altitude(&self) -> Option<f32> {
+        self.state.map(|s| s.altitude_m)
+    }
+
+    /// Forgets the filter state, e.g. after a gap in the samples.
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// Audio tone for a vertical speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tone {
+    pub frequency_hz: f32,
+    /// Climbs are traditionally signalled with beeps, sinks with a
+    /// continuous tone.
+    pub beeping: bool,
+}
+
+/// Maps vertical speed to an audio tone, as variometers traditionally do.
+///
+/// Between the sink and climb thresholds the variometer stays silent.
+/// Otherwise the frequency rises linearly with the vertical speed, clamped
+/// to the configured range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    /// Climb rate in m/s from which on beeps are emitted.
+    pub climb_threshold: f32,
+    /// Sink rate in m/s (negative) below which a continuous tone is emitted.
+    pub sink_threshold: f32,
+    /// Frequency at 0 m/s.
+    pub base_hz: f32,
+    pub hz_per_mps: f32,
+    pub min_hz: f32,
+    pub max_hz: f32,
+}
+
+impl ToneMapping {
+    pub fn tone(&self, vertical_speed: f32) -> Option<Tone> {
+        let beeping = if vertical_speed >= self.climb_threshold {
+            true
+        } else if vertical_speed <= self.sink_threshold {
+            false
+        } else {
+            return None;
+        };
+
+        let frequency_hz = (self.base_hz + self.hz_per_mps * vertical_speed)
+            .max(self.min_hz)
+            .min(self.max_hz);
+
+        Some(Tone { frequency_hz, beeping })
+    }
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        ToneMapping {
+            climb_threshold: 0.1,
+            sink_threshold: -2.0,
+            base_hz: 700.0,
+            hz_per_mps: 100.0,
+            min_hz: 200.0,
+            max_hz: 1500.0,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use ms5611::altitude::{self, Altimeter, STANDARD_PRESSURE_PA};
+use ms5611::Ms5611Sample;
+
+fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+    assert!((actual - expected).abs() <= tolerance,
+            "{} != {} (+/- {})", actual, expected, tolerance);
+}
+
+/// Reference values from the ICAO standard atmosphere tables (geopotential
+/// altitude).
+const ISA_TABLE: [(f32, f32); 7] = [
+    (-500.0, 107_477.7),
+    (0.0, 101_325.0),
+    (1_000.0, 89_874.6),
+    (11_000.0, 22_632.1),
+    (15_000.0, 12_044.6),
+    (20_000.0, 5_474.9),
+    (30_000.0, 1_171.9),
+];
+
+#[test]
+fn standard_atmosphere() {
+    for &(altitude_m, pressure_pa) in ISA_TABLE.iter() {
+        assert_close(altitude::pressure_at_altitude(altitude_m),
+                     pressure_pa, pressure_pa * 1e-4);
+        assert_close(altitude::pressure_altitude(pressure_pa), altitude_m, 1.0);
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut altitude_m = -400.0;
+    while altitude_m < 40_000.0 {
+        let pressure_pa = altitude::pressure_at_altitude(altitude_m);
+        assert_close(altitude::pressure_altitude(pressure_pa), altitude_m, 0.5);
+        altitude_m += 250.0;
+    }
+}
+
+#[test]
+fn reference_pressure() {
+    let qfe = Altimeter::new(95_000.0);
+
+    assert_close(qfe.altitude(95_000.0), 0.0, 1e-3);
+    assert_close(qfe.pressure(0.0), 95_000.0, 0.1);
+    assert_close(qfe.altitude(qfe.pressure(1_234.0)), 1_234.0, 0.1);
+
+    let sample = Ms5611Sample { pressure_pa: 89_875, temperature_centi_c: 0 };
+    assert_close(Altimeter::standard().sample_altitude(&sample), 1_000.0, 0.5);
+    assert_eq!(Altimeter::default().reference_pa(), STANDARD_PRESSURE_PA);
+}
@@ -0,0 +1,62 @@
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+mod common;
+
+use common::NoDelay;
+use embedded_hal::i2c::{ErrorKind, I2c as _};
+use embedded_hal_async::i2c::{ErrorType, I2c, Operation};
+use ms5611::sim::SimMs5611;
+use ms5611::{Error, Ms5611Async, Osr, SamplingConfig};
+
+/// Runs a future that never waits on anything but the simulated device,
+/// which answers right away.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// Simulated device on an async I2C bus.
+struct AsyncSim(SimMs5611);
+
+impl ErrorType for AsyncSim {
+    type Error = ErrorKind;
+}
+
+impl I2c for AsyncSim {
+    async fn transaction(&mut self, address: u8,
+                         operations: &mut [Operation<'_>])
+            -> Result<(), ErrorKind> {
+        self.0.transaction(address, operations)
+    }
+}
+
+#[test]
+fn read_sample() {
+    let mut sim = SimMs5611::new(0x77);
+    sim.set_conditions(95_000, 2512);
+
+    block_on(async {
+        let mut ms5611 = Ms5611Async::new(AsyncSim(sim), None).await
+            .unwrap();
+        assert_eq!(ms5611.prom().pressure_sensitivity(), 40127);
+
+        let sample = ms5611.read_sample(Osr::Opt4096, &mut NoDelay).await
+            .unwrap();
+        assert_eq!(sample.temperature_centi_c, 2512);
+        assert_eq!(sample.pressure_pa, 95_000);
+
+        let sampling = SamplingConfig {
+            pressure: Osr::Opt256,
+            temperature: Osr::Opt8192,
+        };
+        assert!(matches!(ms5611.read_sample(sampling, &mut NoDelay).await,
+                         Err(Error::UnsupportedOsr)));
+    });
+}
@@ -29,6 +29,7 @@ fn get_i2c_bus_path(i2c_bus: i32) -> String {
 
 
 #[test]
+#[ignore = "requires an MS5611 on the i2c bus"]
 fn basic() {
     let dev = I2cdev::new(get_i2c_bus_path(get_i2c_bus())).unwrap();
 
@@ -0,0 +1,41 @@
+//! Fixtures shared by the integration tests.
+
+// Each test crate uses a different subset.
+#![allow(dead_code)]
+
+use embedded_hal::delay::DelayNs;
+use ms5611::Prom;
+
+/// Calibration coefficients C1 to C6 of the MS5611-01BA03 datasheet's worked
+/// example.
+pub const DATASHEET_COEFFICIENTS: [u16; 6] =
+    [40127, 36924, 23317, 23282, 33464, 28312];
+
+pub fn datasheet_prom() -> Prom {
+    Prom::new(DATASHEET_COEFFICIENTS)
+}
+
+/// Conversions of the simulated device complete instantly.
+pub struct NoDelay;
+
+impl DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+/// Sums up the requested delays, in milliseconds.
+pub struct TotalDelay(pub u32);
+
+impl DelayNs for TotalDelay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0 += ns / 1_000_000;
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.0 += ms;
+    }
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::datasheet_prom;
+use ms5611::{variant, Osr, Prom, RawSample};
+
+#[test]
+fn datasheet_example() {
+    let sample = datasheet_prom().compensate(9085466, 8569150);
+
+    assert_eq!(sample.temperature_centi_c, 2007);
+    assert_eq!(sample.pressure_pa, 100009);
+}
+
+#[test]
+fn datasheet_intermediate_terms() {
+    let raw = RawSample { d1: 9085466, d2: 8569150, sampling: Osr::Opt4096.into() };
+    let diag = datasheet_prom().compensate_diagnostic(raw);
+
+    assert_eq!(diag.raw, raw);
+    assert_eq!(diag.dt, 2366);
+    assert_eq!(diag.off, 2420281617);
+    assert_eq!(diag.sens, 1315097036);
+    assert_eq!(diag.sample, datasheet_prom().compensate(raw.d1, raw.d2));
+}
+
+#[test]
+fn ms5607_datasheet_example() {
+    let prom = Prom::new([46372, 43981, 29059, 27842, 31553, 28165]);
+    let raw = RawSample { d1: 6465444, d2: 8077636, sampling: Osr::Opt4096.into() };
+    let diag = prom.compensate_diagnostic_for::<variant::Ms5607>(raw);
+
+    assert_eq!(diag.dt, 68);
+    assert_eq!(diag.off, 5764707214);
+    assert_eq!(diag.sens, 3039050829);
+    assert_eq!(diag.sample.temperature_centi_c, 2000);
+    assert_eq!(diag.sample.pressure_pa, 110002);
+}
+
+fn raw(d1: u32, d2: u32) -> RawSample {
+    RawSample { d1, d2, sampling: Osr::Opt4096.into() }
+}
+
+#[test]
+fn ms5637_datasheet_example() {
+    let prom = Prom::new_for::<variant::Ms5637>(
+        [46372, 43981, 29059, 27842, 31553, 28165]);
+    let diag = prom.compensate_diagnostic_for::<variant::Ms5637>(
+        raw(6465444, 8077636));
+
+    assert_eq!(diag.dt, 68);
+    assert_eq!(diag.off, 5764707214);
+    assert_eq!(diag.sens, 3039050829);
+    assert_eq!(diag.sample.temperature_centi_c, 2000);
+    assert_eq!(diag.sample.pressure_pa, 110002);
+}
+
+#[test]
+fn ms5837_30ba_datasheet_example() {
+    let prom = Prom::new_for::<variant::Ms5837Ba30>(
+        [34982, 36352, 20328, 22354, 26646, 26146]);
+    let diag = prom.compensate_diagnostic_for::<variant::Ms5837Ba30>(
+        raw(4958179, 6815414));
+
+    assert_eq!(diag.dt, -5962);
+    assert_eq!(diag.off, 2381322923);
+    assert_eq!(diag.sens, 1145816530);
+    assert_eq!(diag.sample.temperature_centi_c, 1981);
+    // 3999.8 mbar, resolved in steps of 10 Pa.
+    assert_eq!(diag.sample.pressure_pa, 399980);
+}
+
+#[test]
+fn ms5837_02ba_example() {
+    // The MS5637 example, the parts share the first order compensation.
+    let prom = Prom::new_for::<variant::Ms5837Ba02>(
+        [46372, 43981, 29059, 27842, 31553, 28165]);
+    let diag = prom.compensate_diagnostic_for::<variant::Ms5837Ba02>(
+        raw(6465444, 8077636));
+
+    assert_eq!(diag.dt, 68);
+    assert_eq!(diag.off, 5764707214);
+    assert_eq!(diag.sens, 3039050829);
+    assert_eq!(diag.sample.temperature_centi_c, 2000);
+    assert_eq!(diag.sample.pressure_pa, 110002);
+}
+
+// The following vectors were computed with an independent implementation of
+// the datasheet formulas, to cover the other temperature ranges.
+
+#[test]
+fn ms5803_01ba() {
+    let prom = Prom::new_for::<variant::Ms5803Ba01>(
+        [46546, 42845, 29751, 29457, 32745, 29059]);
+    let compensate = |d1, d2| {
+        prom.compensate_diagnostic_for::<variant::Ms5803Ba01>(raw(d1, d2))
+    };
+
+    let diag = compensate(8365300, 8387300);
+    assert_eq!(diag.dt, 4580);
+    assert_eq!(diag.off, 2808943928);
+    assert_eq!(diag.sens, 1525751591);
+    assert_eq!(diag.sample.temperature_centi_c, 2015);
+    assert_eq!(diag.sample.pressure_pa, 100009);
+
+    // Low temperature
+    let diag = compensate(8459800, 8100000);
+    assert_eq!(diag.off, 2739945571);
+    assert_eq!(diag.sens, 1491522717);
+    assert_eq!(diag.sample.temperature_centi_c, 983);
+    assert_eq!(diag.sample.pressure_pa, 99999);
+
+    // High temperature (>= 45 °C)
+    let diag = compensate(7682700, 9248720);
+    assert_eq!(diag.sens, 1625892507);
+    assert_eq!(diag.sample.temperature_centi_c, 4999);
+    assert_eq!(diag.sample.pressure_pa, 89999);
+}
+
+#[test]
+fn ms5637_high_temperature() {
+    let prom = Prom::new_for::<variant::Ms5637>(
+        [46372, 43981, 29059, 27842, 31553, 28165]);
+    let diag = prom.compensate_diagnostic_for::<variant::Ms5637>(
+        raw(6036400, 8673336));
+
+    assert_eq!(diag.dt, 595768);
+    // First order 4000, T2 is applied above 20 °C as well.
+    assert_eq!(diag.sample.temperature_centi_c, 3994);
+    assert_eq!(diag.sample.pressure_pa, 94999);
+}
+
+#[test]
+fn ms5837_30ba_high_temperature() {
+    let prom = Prom::new_for::<variant::Ms5837Ba30>(
+        [34982, 36352, 20328, 22354, 26646, 26146]);
+    let diag = prom.compensate_diagnostic_for::<variant::Ms5837Ba30>(
+        raw(4584200, 7463076));
+
+    assert_eq!(diag.dt, 641700);
+    assert_eq!(diag.off, 2494181561);
+    assert_eq!(diag.sens, 1197245166);
+    assert_eq!(diag.sample.temperature_centi_c, 3995);
+    assert_eq!(diag.sample.pressure_pa, 150020);
+}
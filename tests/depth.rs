@@ -0,0 +1,29 @@
+use ms5611::depth::{DepthGauge, Fluid};
+use ms5611::Ms5611Sample;
+
+fn sample(pressure_pa: i32) -> Ms5611Sample {
+    Ms5611Sample { pressure_pa, temperature_centi_c: 1500 }
+}
+
+#[test]
+fn depth_from_surface_sample() {
+    let mut gauge = DepthGauge::from_surface_sample(Fluid::SaltWater,
+                                                    &sample(101_000));
+
+    assert_eq!(gauge.sample_depth(&sample(101_000)), 0.0);
+    // 10 m of sea water is ~1 bar.
+    assert!((gauge.sample_depth(&sample(201_909)) - 10.0).abs() < 1e-3);
+
+    gauge.set_surface(&sample(100_000));
+    assert!((gauge.depth(gauge.pressure(42.0)) - 42.0).abs() < 1e-3);
+}
+
+#[test]
+fn fluid_density() {
+    let fresh = DepthGauge::new(Fluid::FreshWater, 101_325.0);
+    let custom = DepthGauge::new(Fluid::Custom(997.0), 101_325.0);
+
+    assert_eq!(fresh.depth(150_000.0), custom.depth(150_000.0));
+    assert!(fresh.depth(150_000.0) >
+            DepthGauge::new(Fluid::SaltWater, 101_325.0).depth(150_000.0));
+}
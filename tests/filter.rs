@@ -0,0 +1,65 @@
+use ms5611::filter::{RateGate, SpikeFilter};
+use ms5611::Ms5611Sample;
+
+fn sample(pressure_pa: i32) -> Ms5611Sample {
+    Ms5611Sample {
+        pressure_pa,
+        temperature_centi_c: 2000,
+    }
+}
+
+#[test]
+fn median() {
+    let mut filter = SpikeFilter::<3>::new(None);
+
+    assert_eq!(filter.update(0, &sample(100_000)), Some(sample(100_000)));
+    // The average of the middle values for an even number of samples.
+    assert_eq!(filter.update(10, &sample(100_010)), Some(sample(100_005)));
+    // Without the gate, the spike is accepted but filtered by the median.
+    assert_eq!(filter.update(20, &sample(90_000)), Some(sample(100_000)));
+    assert_eq!(filter.update(30, &sample(100_020)), Some(sample(100_010)));
+    assert_eq!(filter.rejected(), 0);
+}
+
+#[test]
+fn rate_gate() {
+    let gate = RateGate {
+        max_rate_pa_per_s: 1000,
+        tolerance_pa: 10,
+        max_consecutive_rejections: 5,
+    };
+    let mut filter = SpikeFilter::<1>::new(Some(gate));
+
+    assert!(filter.update(0, &sample(100_000)).is_some());
+    // Up to 10 + 1000 * 0.01 Pa within 10 ms.
+    assert!(filter.update(10, &sample(100_020)).is_some());
+    assert!(filter.update(20, &sample(100_041)).is_none());
+    assert!(filter.update(20, &sample(99_500)).is_none());
+    assert_eq!(filter.rejected(), 2);
+
+    // The allowed jump grows with the time since the last accepted sample.
+    assert_eq!(filter.update(30, &sample(100_041)), Some(sample(100_041)));
+    assert!(filter.update(530, &sample(100_550)).is_some());
+
+    filter.clear_rejected();
+    assert_eq!(filter.rejected(), 0);
+
+    // After a reset, any sample is accepted.
+    filter.reset();
+    assert!(filter.update(540, &sample(50_000)).is_some());
+}
+
+#[test]
+fn spike_first() {
+    let mut filter = SpikeFilter::<3>::new(Some(RateGate::default()));
+
+    assert!(filter.update(0, &sample(50_000)).is_some());
+    for i in 1 ..= 5 {
+        assert!(filter.update(i * 10, &sample(100_000)).is_none());
+    }
+    assert_eq!(filter.rejected(), 5);
+
+    // The filter starts over, dropping the spike from the window.
+    assert_eq!(filter.update(60, &sample(100_000)), Some(sample(100_000)));
+    assert_eq!(filter.rejected(), 5);
+}
@@ -0,0 +1,36 @@
+use ms5611::fusion::{AltitudeKalman, KalmanConfig};
+
+/// Deterministic noise in [-amplitude, amplitude].
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self, amplitude: f32) -> f32 {
+        self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        ((self.0 >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0) * amplitude
+    }
+}
+
+#[test]
+fn converges_with_biased_accelerometer() {
+    let dt = 0.01;
+    let bias = 0.3;
+    let mut noise = Noise(1);
+    let mut kalman = AltitudeKalman::new(KalmanConfig::default(), 100.0);
+
+    let (mut altitude, mut velocity) = (100.0f32, 0.0f32);
+    for i in 0 .. 6000 {
+        // Climb for 2 s, then hold the vertical speed.
+        let accel = if i < 200 { 0.5 } else { 0.0 };
+        altitude += velocity * dt + accel * dt * dt / 2.0;
+        velocity += accel * dt;
+
+        kalman.predict(accel + bias + noise.next(0.2), dt);
+        kalman.update(altitude + noise.next(0.3));
+    }
+
+    assert!((kalman.accel_bias() - bias).abs() < 0.05, "{}", kalman.accel_bias());
+    assert!((kalman.vertical_speed() - velocity).abs() < 0.1,
+            "{} != {}", kalman.vertical_speed(), velocity);
+    assert!((kalman.altitude() - altitude).abs() < 0.3,
+            "{} != {}", kalman.altitude(), altitude);
+}
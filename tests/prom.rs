@@ -0,0 +1,83 @@
+mod common;
+
+use common::{datasheet_prom, DATASHEET_COEFFICIENTS};
+use ms5611::variant::{self, PromLayout};
+use ms5611::{Error, Prom};
+
+#[test]
+fn words_round_trip() {
+    let prom = datasheet_prom();
+    let words = prom.to_words();
+
+    assert_eq!(words[1 .. 7], DATASHEET_COEFFICIENTS);
+    assert_eq!(Prom::from_words(words), Ok(prom));
+}
+
+#[test]
+fn accessors() {
+    let prom = datasheet_prom();
+
+    assert_eq!(prom.pressure_sensitivity(), 40127);
+    assert_eq!(prom.pressure_offset(), 36924);
+    assert_eq!(prom.temp_coef_pressure_sensitivity(), 23317);
+    assert_eq!(prom.temp_coef_pressure_offset(), 23282);
+    assert_eq!(prom.temp_ref(), 33464);
+    assert_eq!(prom.temp_coef_temp(), 28312);
+}
+
+#[test]
+fn crc_mismatch() {
+    let mut words = datasheet_prom().to_words();
+    words[3] ^= 0x0100;
+
+    match Prom::from_words(words) {
+        Err(Error::Crc { expected, computed }) => {
+            assert_eq!(expected, datasheet_prom().crc());
+            assert_ne!(expected, computed);
+        },
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn reserved_bits_excluded_from_crc() {
+    // Bits 4-7 of word 7 are reserved and not covered by the CRC.
+    let mut words = datasheet_prom().to_words();
+    words[7] |= 0x00a0;
+
+    let prom = Prom::from_words(words).unwrap();
+    assert_eq!(prom.crc(), datasheet_prom().crc());
+    assert_eq!(prom.to_words(), words);
+}
+
+#[test]
+fn blank_prom() {
+    assert_eq!(Prom::from_words([0; 8]), Err(Error::InvalidProm));
+}
+
+#[test]
+fn seven_word_layout() {
+    let prom = Prom::new_for::<variant::Ms5837Ba30>(
+        [34982, 36352, 20328, 22354, 26646, 26146]);
+    let words = prom.to_words();
+
+    assert_eq!(prom.layout(), PromLayout::CrcInWord0);
+    assert_eq!(words[0] >> 12, prom.crc() as u16);
+    assert_eq!(words[7], 0);
+    assert_eq!(Prom::from_words_for::<variant::Ms5837Ba30>(words), Ok(prom));
+
+    let mut corrupted = words;
+    corrupted[5] ^= 0x0010;
+    assert!(matches!(Prom::from_words_for::<variant::Ms5837Ba30>(corrupted),
+                     Err(Error::Crc { .. })));
+}
+
+#[test]
+fn seven_word_layout_rejects_word_7() {
+    let mut words = Prom::new_for::<variant::Ms5837Ba30>(
+        [34982, 36352, 20328, 22354, 26646, 26146]).to_words();
+    words[7] = 0x1234;
+
+    assert_eq!(Prom::from_words_for::<variant::Ms5837Ba30>(words),
+               Err(Error::InvalidProm));
+}
@@ -0,0 +1,87 @@
+//! Checks the integer compensation against a floating point implementation
+//! of the datasheet formulas, over the sensor's full operating range.
+
+mod common;
+
+use common::DATASHEET_COEFFICIENTS;
+use ms5611::sim::SimMs5611;
+use ms5611::Prom;
+
+const PROMS: [[u16; 6]; 2] = [
+    DATASHEET_COEFFICIENTS,
+    [53201, 51842, 32005, 28714, 31402, 27456],
+];
+
+/// MS5611 datasheet compensation with real arithmetic.
+/// Returns (pressure in Pa, temperature in celsius * 100).
+fn reference(c: &[u16; 6], d1: u32, d2: u32) -> (f64, f64) {
+    let c: Vec<f64> = c.iter().map(|&c| c as f64).collect();
+    let (d1, d2) = (d1 as f64, d2 as f64);
+    let pow2 = |n: i32| 2f64.powi(n);
+
+    let dt = d2 - c[4] * pow2(8);
+    let mut temp = 2000.0 + dt * c[5] / pow2(23);
+    let mut off = c[1] * pow2(16) + c[3] * dt / pow2(7);
+    let mut sens = c[0] * pow2(15) + c[2] * dt / pow2(8);
+
+    let (mut t2, mut off2, mut sens2) = (0.0, 0.0, 0.0);
+    if temp < 2000.0 {
+        t2 = dt * dt / pow2(31);
+        off2 = 5.0 * (temp - 2000.0).powi(2) / 2.0;
+        sens2 = 5.0 * (temp - 2000.0).powi(2) / 4.0;
+        if temp < -1500.0 {
+            off2 += 7.0 * (temp + 1500.0).powi(2);
+            sens2 += 11.0 * (temp + 1500.0).powi(2) / 2.0;
+        }
+    }
+    temp -= t2;
+    off -= off2;
+    sens -= sens2;
+
+    ((d1 * sens / pow2(21) - off) / pow2(15), temp)
+}
+
+fn check(coefficients: &[u16; 6], d1: u32, d2: u32) {
+    let sample = Prom::new(*coefficients).compensate(d1, d2);
+    let (pressure, temperature) = reference(coefficients, d1, d2);
+
+    // The integer math truncates each term, which accumulates to about 2 Pa
+    // in the worst case.
+    assert!((sample.pressure_pa as f64 - pressure).abs() <= 3.0,
+            "D1={} D2={}: {} != {}", d1, d2, sample.pressure_pa, pressure);
+    assert!((sample.temperature_centi_c as f64 - temperature).abs() <= 1.0,
+            "D1={} D2={}: {} != {}", d1, d2, sample.temperature_centi_c,
+            temperature);
+}
+
+#[test]
+fn operating_range() {
+    for coefficients in PROMS.iter() {
+        let mut sim = SimMs5611::with_prom(0x77, &Prom::new(*coefficients));
+
+        // -40..85 °C, 10..1200 mbar. The simulation only provides the raw
+        // values: it inverts the integer compensation, so comparing against
+        // its conditions would be circular.
+        for temperature in (-4000 ..= 8500).step_by(250) {
+            for pressure in (1000 ..= 120_000).step_by(1700) {
+                sim.set_conditions(pressure, temperature);
+                let (d1, d2) = sim.raw_values();
+
+                check(coefficients, d1, d2);
+            }
+        }
+    }
+}
+
+#[test]
+fn very_low_temperature_branch() {
+    let coefficients = &PROMS[0];
+    let mut sim = SimMs5611::with_prom(0x77, &Prom::new(*coefficients));
+    sim.set_conditions(50_000, -3000);
+    let (d1, d2) = sim.raw_values();
+
+    let first_order = 2000 + (((d2 as i64 - ((coefficients[4] as i64) << 8))
+        * coefficients[5] as i64) >> 23);
+    assert!(first_order < -1500);
+    check(coefficients, d1, d2);
+}
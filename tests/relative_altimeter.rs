@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+mod common;
+
+use common::NoDelay;
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+use ms5611::altitude::RelativeAltimeter;
+use ms5611::sim::SimMs5611;
+use ms5611::{Ms5611, Osr};
+
+/// Simulated device shared with the test, which can queue the pressures of
+/// the following conversions.
+struct Env {
+    sim: SimMs5611,
+    pressures: VecDeque<i32>,
+}
+
+struct Shared<'a>(&'a RefCell<Env>);
+
+impl ErrorType for Shared<'_> {
+    type Error = ErrorKind;
+}
+
+impl I2c for Shared<'_> {
+    fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>])
+            -> Result<(), ErrorKind> {
+        let mut env = self.0.borrow_mut();
+        let starts_d1 = operations.iter().any(|op| {
+            matches!(op, Operation::Write(bytes) if bytes[0] & 0xf0 == 0x40)
+        });
+        if starts_d1 {
+            if let Some(pressure_pa) = env.pressures.pop_front() {
+                env.sim.set_conditions(pressure_pa, 1500);
+            }
+        }
+        env.sim.transaction(address, operations)
+    }
+}
+
+fn set_pressures(env: &RefCell<Env>, pressures: &[i32]) {
+    env.borrow_mut().pressures.extend(pressures);
+}
+
+/// Hypsometric height for a 15 °C ground temperature.
+fn expected_height(pressure_pa: f32, ground_pa: f32) -> f32 {
+    288.15 / 0.0065 * (1.0 - (pressure_pa / ground_pa).powf(0.190_263))
+}
+
+#[test]
+fn zero_and_height() {
+    let env = RefCell::new(Env {
+        sim: SimMs5611::new(0x77),
+        pressures: VecDeque::new(),
+    });
+    let ms5611 = Ms5611::new(Shared(&env), None).unwrap();
+
+    // The ground reference is the average of the samples.
+    set_pressures(&env, &[100_000, 100_010, 100_020, 100_030]);
+    let mut altimeter =
+        RelativeAltimeter::new(ms5611, Osr::Opt4096, 4, &mut NoDelay).unwrap();
+    assert_eq!(altimeter.ground_pressure_pa(), 100_015.0);
+    assert!((altimeter.ground_temperature_c() - 15.0).abs() < 0.01);
+
+    set_pressures(&env, &[98_800]);
+    let sample = altimeter.read_sample(&mut NoDelay).unwrap();
+    assert_eq!(sample.sample.pressure_pa, 98_800);
+    let expected = expected_height(98_800.0, 100_015.0);
+    assert!((sample.height_m - expected).abs() < 0.1,
+            "{} != {}", sample.height_m, expected);
+    // Roughly 8.5 m per hPa close to sea level.
+    assert!((sample.height_m - 103.0).abs() < 1.0);
+
+    // Re-zeroing moves the reference to the current pressure.
+    set_pressures(&env, &[98_800, 98_800]);
+    altimeter.zero(2, &mut NoDelay).unwrap();
+    assert_eq!(altimeter.ground_pressure_pa(), 98_800.0);
+    assert!(altimeter.height(98_800.0).abs() < 0.01);
+    assert!(altimeter.height(100_015.0) < -100.0);
+}
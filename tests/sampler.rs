@@ -0,0 +1,91 @@
+use core::convert::Infallible;
+
+mod common;
+
+use common::datasheet_prom;
+use ms5611::sampler::ContinuousSampler;
+use ms5611::{Interface, Ms5611, Ms5611Sample, Poll};
+
+/// Converts D1 values from a list and a fixed D2.
+struct Scripted {
+    d1: &'static [u32],
+    adc: u32,
+}
+
+const D2: u32 = 8_569_150;
+
+impl Interface for Scripted {
+    type Error = Infallible;
+
+    fn write(&mut self, cmd: u8) -> Result<(), Infallible> {
+        if cmd & 0xf0 == 0x40 {
+            self.adc = self.d1[0];
+            self.d1 = &self.d1[1 ..];
+        } else if cmd & 0xf0 == 0x50 {
+            self.adc = D2;
+        }
+        Ok(())
+    }
+
+    fn write_read(&mut self, _cmd: u8, buf: &mut [u8])
+            -> Result<(), Infallible> {
+        buf.copy_from_slice(&self.adc.to_be_bytes()[1 ..]);
+        Ok(())
+    }
+}
+
+fn sampler<const N: usize>(d1: &'static [u32], decimation: u16)
+        -> ContinuousSampler<Scripted, N> {
+    let bus = Scripted { d1, adc: 0 };
+    ContinuousSampler::new(Ms5611::from_interface_with_prom(bus, datasheet_prom()),
+                           decimation)
+}
+
+/// Polls until the next decimated sample, returning it along with the time.
+fn next_sample<const N: usize>(sampler: &mut ContinuousSampler<Scripted, N>,
+                               mut now: u32) -> (u32, Ms5611Sample) {
+    loop {
+        match sampler.poll(now).unwrap() {
+            Poll::Pending { ready_at } => now = ready_at,
+            Poll::Ready(sample) => return (now, sample),
+        }
+    }
+}
+
+#[test]
+fn decimation() {
+    let d1 = &[9_085_400, 9_085_500, 9_085_600, 9_085_700];
+    let mut sampler = sampler::<4>(d1, 2);
+
+    // Back-to-back conversions at OSR 4096, 10 ms each plus 1 ms for the
+    // clock granularity.
+    let (now, sample) = next_sample(&mut sampler, 0);
+    assert_eq!(now, 44);
+    assert_eq!(sample, datasheet_prom().compensate(9_085_450, D2));
+
+    let (now, sample) = next_sample(&mut sampler, now);
+    assert_eq!(now, 88);
+    assert_eq!(sample, datasheet_prom().compensate(9_085_650, D2));
+}
+
+#[test]
+fn ring_buffer() {
+    let d1 = &[9_085_000, 9_085_300, 9_099_999, 9_085_600];
+    let mut sampler = sampler::<3>(d1, 4);
+    assert!(sampler.moving_average().is_none());
+    assert!(sampler.median().is_none());
+
+    next_sample(&mut sampler, 0);
+    assert_eq!(sampler.len(), 3);
+    let d1: Vec<u32> = sampler.raw_samples().map(|raw| raw.d1).collect();
+    assert_eq!(d1, [9_085_300, 9_099_999, 9_085_600]);
+    assert_eq!(sampler.latest().unwrap().d1, 9_085_600);
+
+    assert_eq!(sampler.moving_average().unwrap(),
+               datasheet_prom().compensate(9_090_300, D2));
+    // The spike doesn't affect the median.
+    assert_eq!(sampler.median().unwrap(), datasheet_prom().compensate(9_085_600, D2));
+
+    sampler.clear();
+    assert!(sampler.is_empty());
+}
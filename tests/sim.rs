@@ -0,0 +1,218 @@
+use std::cell::Cell;
+
+mod common;
+
+use embedded_hal::delay::DelayNs;
+use common::{NoDelay, TotalDelay};
+use ms5611::sim::SimMs5611;
+use ms5611::{ConversionSpan, Error, Ms5611, Osr, Poll, Prom, RetryPolicy,
+             SamplingConfig};
+
+#[test]
+fn read_sample() {
+    let mut sim = SimMs5611::new(0x77);
+    sim.set_conditions(95_000, 2512);
+
+    let mut ms5611 = Ms5611::new(sim, None).unwrap();
+    let sample = ms5611.read_sample(Osr::Opt4096, &mut NoDelay).unwrap();
+
+    assert_eq!(sample.temperature_centi_c, 2512);
+    assert_eq!(sample.pressure_pa, 95_000);
+    ms5611.reset(&mut NoDelay).unwrap();
+}
+
+#[test]
+fn low_temperature() {
+    let mut sim = SimMs5611::new(0x76);
+    sim.set_conditions(30_000, -2500);
+
+    let mut ms5611 = Ms5611::new(sim, Some(0x76)).unwrap();
+    let sample = ms5611.read_sample(Osr::Opt256, &mut NoDelay).unwrap();
+
+    assert_eq!(sample.temperature_centi_c, -2500);
+    assert_eq!(sample.pressure_pa, 30_000);
+}
+
+#[test]
+fn prom_is_read() {
+    let prom = Prom::new([45000, 40000, 25000, 24000, 32000, 27000]);
+    let ms5611 = Ms5611::new(SimMs5611::with_prom(0x77, &prom), None).unwrap();
+
+    assert_eq!(ms5611.prom(), &prom);
+}
+
+#[test]
+fn crc_mismatch() {
+    let mut words = Prom::new([45000, 40000, 25000, 24000, 32000, 27000])
+        .to_words();
+    words[2] ^= 0x0400;
+
+    match Ms5611::new(SimMs5611::with_prom_words(0x77, words), None) {
+        Err(Error::Crc { .. }) => {},
+        other => panic!("unexpected {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn wrong_address() {
+    assert!(matches!(Ms5611::new(SimMs5611::new(0x77), Some(0x76)),
+                     Err(Error::Bus(_))));
+}
+
+#[test]
+fn adc_read_twice() {
+    let mut ms5611 = Ms5611::new(SimMs5611::new(0x77), None).unwrap();
+
+    ms5611.start_pressure_conversion(Osr::Opt1024).unwrap();
+    ms5611.read_adc().unwrap();
+    assert!(matches!(ms5611.read_adc(), Err(Error::AdcNotReady)));
+}
+
+#[test]
+fn premature_read_fails_without_retries() {
+    let mut sim = SimMs5611::new(0x77);
+    sim.set_premature_reads(1);
+    let mut ms5611 = Ms5611::new(sim, None).unwrap();
+
+    assert!(matches!(ms5611.read_sample(Osr::Opt4096, &mut NoDelay),
+                     Err(Error::AdcNotReady)));
+}
+
+#[test]
+fn premature_reads_are_retried() {
+    let mut sim = SimMs5611::new(0x77);
+    sim.set_premature_reads(2);
+    let mut ms5611 = Ms5611::new(sim, None).unwrap();
+    ms5611.set_retry_policy(RetryPolicy { retries: 2, extra_delay_ms: 1 });
+
+    let mut delay = TotalDelay(0);
+    let sample = ms5611.read_sample(Osr::Opt4096, &mut delay).unwrap();
+    assert_eq!(sample.pressure_pa, 101_325);
+    // Three D1 conversions, the restarted ones awaited longer, one D2.
+    assert_eq!(delay.0, 10 + 2 * 11 + 10);
+
+    // The retries are per conversion.
+    let mut sim = SimMs5611::new(0x77);
+    sim.set_premature_reads(3);
+    let mut ms5611 = Ms5611::new(sim, None).unwrap();
+    ms5611.set_retry_policy(RetryPolicy { retries: 2, extra_delay_ms: 1 });
+    assert!(matches!(ms5611.read_sample(Osr::Opt4096, &mut NoDelay),
+                     Err(Error::AdcNotReady)));
+}
+
+#[test]
+fn poll() {
+    let mut ms5611 = Ms5611::new(SimMs5611::new(0x77), None).unwrap();
+    ms5611.set_osr(Osr::Opt2048);
+
+    assert!(matches!(ms5611.poll(100), Ok(Poll::Pending { ready_at: 106 })));
+    assert!(matches!(ms5611.poll(105), Ok(Poll::Pending { ready_at: 106 })));
+    assert!(matches!(ms5611.poll(106), Ok(Poll::Pending { ready_at: 112 })));
+    match ms5611.poll(112) {
+        Ok(Poll::Ready(sample)) => assert_eq!(sample.pressure_pa, 101_325),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn poll_reuses_temperature() {
+    let mut ms5611 = Ms5611::new(SimMs5611::new(0x77), None).unwrap();
+    ms5611.set_sampling(SamplingConfig {
+        pressure: Osr::Opt4096,
+        temperature: Osr::Opt256,
+    });
+    ms5611.set_temperature_ratio(2);
+
+    assert!(matches!(ms5611.poll(0), Ok(Poll::Pending { ready_at: 11 })));
+    assert!(matches!(ms5611.poll(11), Ok(Poll::Pending { ready_at: 13 })));
+    assert!(matches!(ms5611.poll(13), Ok(Poll::Ready(_))));
+
+    // The second pressure conversion reuses the temperature.
+    assert!(matches!(ms5611.poll(13), Ok(Poll::Pending { ready_at: 24 })));
+    assert!(matches!(ms5611.poll(24), Ok(Poll::Ready(_))));
+
+    assert!(matches!(ms5611.poll(24), Ok(Poll::Pending { ready_at: 35 })));
+    assert!(matches!(ms5611.poll(35), Ok(Poll::Pending { ready_at: 37 })));
+}
+
+#[test]
+fn sampling_config() {
+    let mut ms5611 = Ms5611::new(SimMs5611::new(0x77), None).unwrap();
+    let sampling = SamplingConfig {
+        pressure: Osr::Opt4096,
+        temperature: Osr::Opt256,
+    };
+    assert_eq!(sampling.conversion_time_ms(), 11);
+
+    let mut delay = TotalDelay(0);
+    let raw = ms5611.read_raw(sampling, &mut delay).unwrap();
+    assert_eq!(raw.sampling, sampling);
+    assert_eq!(delay.0, 11);
+
+    let sample = ms5611.read_sample(sampling, &mut NoDelay).unwrap();
+    assert_eq!(sample.pressure_pa, 101_325);
+    assert!(matches!(ms5611.read_sample(SamplingConfig {
+                         pressure: Osr::Opt4096,
+                         temperature: Osr::Opt8192,
+                     }, &mut NoDelay),
+                     Err(Error::UnsupportedOsr)));
+}
+
+#[test]
+fn read_scheduled() {
+    let mut ms5611 = Ms5611::new(SimMs5611::new(0x77), None).unwrap();
+    ms5611.set_sampling(SamplingConfig {
+        pressure: Osr::Opt4096,
+        temperature: Osr::Opt256,
+    });
+    ms5611.set_temperature_ratio(3);
+
+    let mut delay = TotalDelay(0);
+    for _ in 0 .. 6 {
+        let sample = ms5611.read_scheduled(&mut delay).unwrap();
+        assert_eq!(sample.pressure_pa, 101_325);
+        assert_eq!(sample.temperature_centi_c, 2000);
+    }
+    // Six pressure and two temperature conversions.
+    assert_eq!(delay.0, 6 * 10 + 2);
+}
+
+#[test]
+fn cached_prom() {
+    let prom = Ms5611::new(SimMs5611::new(0x77), None).unwrap().prom().clone();
+    let mut ms5611 = Ms5611::with_prom(SimMs5611::new(0x77), None, prom);
+    ms5611.verify_prom().unwrap();
+
+    let other = Prom::new([45000, 40000, 25000, 24000, 32000, 27000]);
+    let mut ms5611 = Ms5611::with_prom(SimMs5611::new(0x77), None, other);
+    assert!(matches!(ms5611.verify_prom(), Err(Error::InvalidProm)));
+}
+
+/// Advances a millisecond clock shared with the test.
+struct ClockDelay<'a>(&'a Cell<u32>);
+
+impl DelayNs for ClockDelay<'_> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.set(self.0.get() + ns / 1_000_000);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.set(self.0.get() + ms);
+    }
+}
+
+#[test]
+fn timestamped_sample() {
+    let mut ms5611 = Ms5611::new(SimMs5611::new(0x77), None).unwrap();
+    let now = Cell::new(1000);
+    let sampling = SamplingConfig {
+        pressure: Osr::Opt4096,
+        temperature: Osr::Opt1024,
+    };
+
+    let sample = ms5611.read_sample_timestamped(
+        sampling, &mut ClockDelay(&now), &mut || now.get()).unwrap();
+    assert_eq!(sample.sample.pressure_pa, 101_325);
+    assert_eq!(sample.pressure, ConversionSpan { start: 1000, end: 1010 });
+    assert_eq!(sample.temperature, ConversionSpan { start: 1010, end: 1013 });
+}
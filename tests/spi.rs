@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+
+mod common;
+
+use common::NoDelay;
+use embedded_hal::i2c::{self, I2c};
+use embedded_hal::spi::{self, SpiDevice};
+use ms5611::sim::SimMs5611;
+use ms5611::{Ms5611, Osr};
+
+/// Answers SPI transactions like the device, recording the command bytes.
+struct ScriptedSpi<'a> {
+    sim: SimMs5611,
+    commands: &'a RefCell<Vec<u8>>,
+}
+
+impl spi::ErrorType for ScriptedSpi<'_> {
+    type Error = spi::ErrorKind;
+}
+
+impl SpiDevice for ScriptedSpi<'_> {
+    fn transaction(&mut self, operations: &mut [spi::Operation<'_, u8>])
+            -> Result<(), spi::ErrorKind> {
+        for op in operations {
+            // The device answers the same on both buses.
+            let res = match op {
+                spi::Operation::Write(bytes) => {
+                    self.commands.borrow_mut().extend_from_slice(bytes);
+                    self.sim.write(0x77, bytes)
+                },
+                spi::Operation::Read(buf) => self.sim.read(0x77, buf),
+                _ => return Err(spi::ErrorKind::Other),
+            };
+            res.map_err(|_| spi::ErrorKind::Other)?;
+        }
+        Ok(())
+    }
+}
+
+/// Records the command bytes sent over I2C.
+struct RecordingI2c<'a> {
+    sim: SimMs5611,
+    commands: &'a RefCell<Vec<u8>>,
+}
+
+impl i2c::ErrorType for RecordingI2c<'_> {
+    type Error = i2c::ErrorKind;
+}
+
+impl I2c for RecordingI2c<'_> {
+    fn transaction(&mut self, address: u8,
+                   operations: &mut [i2c::Operation<'_>])
+            -> Result<(), i2c::ErrorKind> {
+        for op in operations.iter() {
+            if let i2c::Operation::Write(bytes) = op {
+                self.commands.borrow_mut().extend_from_slice(bytes);
+            }
+        }
+        self.sim.transaction(address, operations)
+    }
+}
+
+fn sim() -> SimMs5611 {
+    let mut sim = SimMs5611::new(0x77);
+    sim.set_conditions(95_000, 2512);
+    sim
+}
+
+#[test]
+fn reads_prom() {
+    let commands = RefCell::new(Vec::new());
+    let spi = ScriptedSpi { sim: sim(), commands: &commands };
+    let ms5611 = Ms5611::new_spi(spi).unwrap();
+
+    assert_eq!(ms5611.prom().pressure_sensitivity(), 40127);
+    assert_eq!(ms5611.prom().temp_coef_temp(), 28312);
+}
+
+#[test]
+fn same_commands_as_i2c() {
+    let spi_commands = RefCell::new(Vec::new());
+    let spi = ScriptedSpi { sim: sim(), commands: &spi_commands };
+    let mut over_spi = Ms5611::new_spi(spi).unwrap();
+    let i2c_commands = RefCell::new(Vec::new());
+    let i2c = RecordingI2c { sim: sim(), commands: &i2c_commands };
+    let mut over_i2c = Ms5611::new(i2c, None).unwrap();
+
+    let spi_sample = over_spi.read_sample(Osr::Opt1024, &mut NoDelay).unwrap();
+    let i2c_sample = over_i2c.read_sample(Osr::Opt1024, &mut NoDelay).unwrap();
+    assert_eq!(spi_sample, i2c_sample);
+    assert_eq!(spi_sample.pressure_pa, 95_000);
+
+    // PROM words 0 to 7, D1 and D2 at OSR 1024, each followed by an ADC read.
+    let mut expected: Vec<u8> = (0 .. 8).map(|i| 0xa0 + 2 * i).collect();
+    expected.extend_from_slice(&[0x44, 0x00, 0x54, 0x00]);
+    assert_eq!(*spi_commands.borrow(), expected);
+    assert_eq!(*i2c_commands.borrow(), expected);
+}
@@ -0,0 +1,47 @@
+//! Driver behaviour that depends on the sensor variant.
+
+use core::convert::Infallible;
+
+mod common;
+
+use common::TotalDelay;
+use ms5611::{variant, Interface, Ms5611, Osr, Prom};
+
+/// Answers every ADC read with the same conversion result.
+struct Scripted;
+
+impl Interface for Scripted {
+    type Error = Infallible;
+
+    fn write(&mut self, _cmd: u8) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn write_read(&mut self, _cmd: u8, buf: &mut [u8])
+            -> Result<(), Infallible> {
+        buf.copy_from_slice(&6_815_414u32.to_be_bytes()[4 - buf.len() ..]);
+        Ok(())
+    }
+}
+
+fn ms5837_30ba() -> Ms5611<Scripted, variant::Ms5837Ba30> {
+    let prom = Prom::new_for::<variant::Ms5837Ba30>(
+        [34982, 36352, 20328, 22354, 26646, 26146]);
+    Ms5611::from_interface_with_prom(Scripted, prom)
+}
+
+#[test]
+fn osr_8192_conversion_time() {
+    let mut delay = TotalDelay(0);
+    ms5837_30ba().read_sample(Osr::Opt8192, &mut delay).unwrap();
+
+    // The datasheet gives up to 18.08ms per conversion.
+    assert_eq!(delay.0, 40);
+}
+
+#[test]
+#[should_panic(expected = "calibration data of a different part")]
+fn prom_of_different_layout() {
+    let _ = Ms5611::<_, variant::Ms5837Ba30>::from_interface_with_prom(
+        Scripted, common::datasheet_prom());
+}
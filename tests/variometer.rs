@@ -0,0 +1,51 @@
+use ms5611::altitude;
+use ms5611::variometer::{Tone, ToneMapping, Variometer};
+use ms5611::Ms5611Sample;
+
+fn sample_at(altitude_m: f32) -> Ms5611Sample {
+    Ms5611Sample {
+        pressure_pa: altitude::pressure_at_altitude(altitude_m) as i32,
+        temperature_centi_c: 2000,
+    }
+}
+
+#[test]
+fn constant_climb() {
+    let mut vario = Variometer::new(1.0);
+
+    assert_eq!(vario.update(0, &sample_at(500.0)), 0.0);
+
+    let mut vertical_speed = 0.0;
+    for i in 1 ..= 500u32 {
+        // 2 m/s at 50 Hz
+        let altitude_m = 500.0 + 2.0 * i as f32 / 50.0;
+        vertical_speed = vario.update(i * 20, &sample_at(altitude_m));
+    }
+
+    assert!((vertical_speed - 2.0).abs() < 0.1, "{}", vertical_speed);
+    assert_eq!(vario.vertical_speed(), vertical_speed);
+}
+
+#[test]
+fn level_flight_across_clock_wrap() {
+    let mut vario = Variometer::new(0.5);
+    let start = u32::MAX - 1000;
+
+    for i in 0 .. 200u32 {
+        vario.update(start.wrapping_add(i * 20), &sample_at(1000.0));
+    }
+
+    assert!(vario.vertical_speed().abs() < 1e-3);
+}
+
+#[test]
+fn tone_mapping() {
+    let mapping = ToneMapping::default();
+
+    assert_eq!(mapping.tone(0.0), None);
+    assert_eq!(mapping.tone(1.0),
+               Some(Tone { frequency_hz: 800.0, beeping: true }));
+    assert_eq!(mapping.tone(-3.0),
+               Some(Tone { frequency_hz: 400.0, beeping: false }));
+    assert_eq!(mapping.tone(50.0).map(|t| t.frequency_hz), Some(1500.0));
+}